@@ -1,29 +1,87 @@
 use crate::function::Function;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use crate::EvaluationError;
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Element {
     Boolean(bool),
     Integer(i64),
     Float(f64),
     Char(char),
+    String(String),
     Variable(String),
+    Symbol(String),
     Function(Function),
     Array(Vec<Element>),
     Procedure(Vec<Element>),
+    Map(#[cfg_attr(feature = "serde", serde(with = "entries"))] BTreeMap<Key, Element>),
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Key {
+    Boolean(bool),
+    Integer(i64),
+    Char(char),
+    String(String),
+    Symbol(String),
+}
+
+/// Serializes maps as lists of entries, as most formats only allow string keys.
+#[cfg(feature = "serde")]
+mod entries {
+    use super::{Element, Key};
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::BTreeMap;
+
+    pub(super) fn serialize<S: Serializer>(map: &BTreeMap<Key, Element>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(map.iter())
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<Key, Element>, D::Error> {
+        Ok(Vec::<(Key, Element)>::deserialize(deserializer)?.into_iter().collect())
+    }
 }
 
 impl Element {
-    pub(crate) fn is_string(&self) -> bool {
-        if let Element::Array(elements) = self {
-            elements.iter().all(|e| matches!(e, Element::Char(_)))
-        } else {
-            false
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Element::Boolean(_) => "boolean",
+            Element::Integer(_) => "integer",
+            Element::Float(_) => "float",
+            Element::Char(_) => "char",
+            Element::String(_) => "string",
+            Element::Variable(_) => "variable",
+            Element::Symbol(_) => "symbol",
+            Element::Function(_) => "function",
+            Element::Array(_) => "array",
+            Element::Procedure(_) => "procedure",
+            Element::Map(_) => "map",
         }
     }
 
-    fn as_char(&self) -> Result<char, EvaluationError> {
+    pub(crate) fn size(&self) -> usize {
+        size_of::<Element>()
+            + match self {
+                Element::String(s) | Element::Variable(s) | Element::Symbol(s) => s.len(),
+                Element::Array(elements) | Element::Procedure(elements) => {
+                    elements.iter().map(Element::size).sum()
+                }
+                Element::Map(entries) => entries
+                    .iter()
+                    .map(|(k, v)| match k {
+                        Key::String(s) | Key::Symbol(s) => size_of::<Key>() + s.len() + v.size(),
+                        _ => size_of::<Key>() + v.size(),
+                    })
+                    .sum(),
+                _ => 0,
+            }
+    }
+
+    pub(crate) fn as_char(&self) -> Result<char, EvaluationError> {
         if let Element::Char(c) = self {
             Ok(*c)
         } else {
@@ -31,13 +89,42 @@ impl Element {
         }
     }
 
-    pub(crate) fn as_string(&self) -> Result<String, EvaluationError> {
-        if let Element::Array(elements) = self {
-            Ok(elements.iter()
+    pub(crate) fn as_string(&self) -> Result<Cow<'_, str>, EvaluationError> {
+        match self {
+            Element::String(string) => Ok(Cow::Borrowed(string)),
+            Element::Array(elements) => elements.iter()
                 .map(Element::as_char)
-                .collect::<Result<String, EvaluationError>>()?)
-        } else {
-            Err(EvaluationError::NotAString)
+                .collect::<Result<String, EvaluationError>>()
+                .map(Cow::Owned)
+                .map_err(|_| EvaluationError::NotAString),
+            _ => Err(EvaluationError::NotAString),
+        }
+    }
+
+    pub(crate) fn into_string(self) -> Result<String, EvaluationError> {
+        match self {
+            Element::String(string) => Ok(string),
+            element => element.as_string().map(Cow::into_owned),
+        }
+    }
+
+    pub(crate) fn into_array(self) -> Result<Vec<Element>, EvaluationError> {
+        match self {
+            Element::Array(elements) => Ok(elements),
+            Element::String(string) => Ok(string.chars().map(Element::Char).collect()),
+            _ => Err(EvaluationError::InvalidStackElements(None)),
+        }
+    }
+
+    pub(crate) fn new_string(value: &str) -> Element {
+        Element::String(value.to_string())
+    }
+
+    pub(crate) fn to_text(&self) -> String {
+        match self {
+            Element::Char(c) => c.to_string(),
+            Element::String(string) => string.clone(),
+            element => element.to_string(),
         }
     }
 }
@@ -51,25 +138,167 @@ impl Display for Element {
             Element::Float(fl) => write!(f, "{}", fl),
             Element::Char(c) => write!(f, "'{}'", c),
             Element::Variable(v) => write!(f, "${}", v),
+            Element::Symbol(s) => write!(f, ":{}", s),
             Element::Function(fu) => write!(f, "f({})", fu.name()),
+            Element::String(string) => write!(f, "\"{}\"", string),
             Element::Array(elements) => {
-                if self.is_string() {
-                    write!(f, "\"{}\"", self.as_string().unwrap())
-                } else {
-                    f.write_str("[ ")?;
-                    for e in elements {
-                        write!(f, "{} ", e)?;
-                    }
-                    f.write_str("]")
+                f.write_str("[ ")?;
+                for e in elements {
+                    write!(f, "{} ", e)?;
                 }
+                f.write_str("]")
             }
             Element::Procedure(elements) => {
-                f.write_str("{{ ")?;
+                f.write_str("{ ")?;
                 for e in elements {
                     write!(f, "{} ", e)?;
                 }
-                f.write_str("}}")
+                f.write_str("}")
             }
+            Element::Map(entries) => {
+                f.write_str("#{ ")?;
+                for (k, v) in entries {
+                    write!(f, "{} {} ", k, v)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+impl TryFrom<Element> for Key {
+    type Error = EvaluationError;
+
+    fn try_from(value: Element) -> Result<Self, Self::Error> {
+        match value {
+            Element::Boolean(b) => Ok(Key::Boolean(b)),
+            Element::Integer(i) => Ok(Key::Integer(i)),
+            Element::Char(c) => Ok(Key::Char(c)),
+            Element::String(s) => Ok(Key::String(s)),
+            Element::Symbol(s) => Ok(Key::Symbol(s)),
+            _ => Err(EvaluationError::NotAKey),
+        }
+    }
+}
+
+impl From<Key> for Element {
+    fn from(value: Key) -> Self {
+        match value {
+            Key::Boolean(b) => Element::Boolean(b),
+            Key::Integer(i) => Element::Integer(i),
+            Key::Char(c) => Element::Char(c),
+            Key::String(s) => Element::String(s),
+            Key::Symbol(s) => Element::Symbol(s),
+        }
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Element::from(self.clone()).fmt(f)
+    }
+}
+
+impl From<i64> for Element {
+    fn from(value: i64) -> Self {
+        Element::Integer(value)
+    }
+}
+
+impl From<f64> for Element {
+    fn from(value: f64) -> Self {
+        Element::Float(value)
+    }
+}
+
+impl From<bool> for Element {
+    fn from(value: bool) -> Self {
+        Element::Boolean(value)
+    }
+}
+
+impl From<char> for Element {
+    fn from(value: char) -> Self {
+        Element::Char(value)
+    }
+}
+
+impl From<String> for Element {
+    fn from(value: String) -> Self {
+        Element::String(value)
+    }
+}
+
+impl<T: Into<Element>> From<Vec<T>> for Element {
+    fn from(value: Vec<T>) -> Self {
+        Element::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl TryFrom<Element> for i64 {
+    type Error = EvaluationError;
+
+    fn try_from(value: Element) -> Result<Self, Self::Error> {
+        match value {
+            Element::Integer(i) => Ok(i),
+            element => Err(EvaluationError::UnexpectedElement("integer", element)),
+        }
+    }
+}
+
+impl TryFrom<Element> for f64 {
+    type Error = EvaluationError;
+
+    fn try_from(value: Element) -> Result<Self, Self::Error> {
+        match value {
+            Element::Float(f) => Ok(f),
+            Element::Integer(i) => Ok(i as f64),
+            element => Err(EvaluationError::UnexpectedElement("number", element)),
+        }
+    }
+}
+
+impl TryFrom<Element> for bool {
+    type Error = EvaluationError;
+
+    fn try_from(value: Element) -> Result<Self, Self::Error> {
+        match value {
+            Element::Boolean(b) => Ok(b),
+            element => Err(EvaluationError::UnexpectedElement("boolean", element)),
+        }
+    }
+}
+
+impl TryFrom<Element> for char {
+    type Error = EvaluationError;
+
+    fn try_from(value: Element) -> Result<Self, Self::Error> {
+        match value {
+            Element::Char(c) => Ok(c),
+            element => Err(EvaluationError::UnexpectedElement("char", element)),
+        }
+    }
+}
+
+impl TryFrom<Element> for String {
+    type Error = EvaluationError;
+
+    fn try_from(value: Element) -> Result<Self, Self::Error> {
+        match value.as_string() {
+            Ok(string) => Ok(string.into_owned()),
+            Err(_) => Err(EvaluationError::UnexpectedElement("string", value)),
+        }
+    }
+}
+
+impl<T: TryFrom<Element, Error = EvaluationError>> TryFrom<Element> for Vec<T> {
+    type Error = EvaluationError;
+
+    fn try_from(value: Element) -> Result<Self, Self::Error> {
+        match value {
+            Element::Array(elements) => elements.into_iter().map(T::try_from).collect(),
+            Element::String(string) => string.chars().map(|c| T::try_from(Element::Char(c))).collect(),
+            element => Err(EvaluationError::UnexpectedElement("array", element)),
         }
     }
 }
@@ -117,6 +346,51 @@ mod tests {
         assert_matches!(Element::try_from("5"), Ok(Element::Integer(5)));
     }
 
+    #[test]
+    fn displays_map_in_key_order() {
+        let map = Element::Map(BTreeMap::from([
+            (Key::String("b".to_string()), Element::Integer(2)),
+            (Key::String("a".to_string()), Element::Integer(1)),
+            (Key::Integer(3), Element::Char('c')),
+        ]));
+        assert_eq!(map.to_string(), "#{ 3 'c' \"a\" 1 \"b\" 2 }");
+    }
+
+    #[test]
+    fn converts_from_rust_values() {
+        assert_eq!(Element::from(3), Element::Integer(3));
+        assert_eq!(Element::from('c'), Element::Char('c'));
+        assert_eq!(Element::from("ab".to_string()), Element::String("ab".to_string()));
+        assert_eq!(
+            Element::from(vec![vec![true], vec![]]),
+            Element::Array(vec![Element::Array(vec![Element::Boolean(true)]), Element::Array(vec![])])
+        );
+    }
+
+    #[test]
+    fn converts_into_rust_values() {
+        assert_eq!(i64::try_from(Element::Integer(3)), Ok(3));
+        assert_eq!(f64::try_from(Element::Integer(3)), Ok(3.0));
+        assert_eq!(String::try_from(Element::Array(vec![Element::Char('a')])), Ok("a".to_string()));
+        assert_eq!(Vec::<char>::try_from(Element::from(vec!['a', 'b'])), Ok(vec!['a', 'b']));
+        assert_eq!(Vec::<char>::try_from(Element::new_string("ab")), Ok(vec!['a', 'b']));
+        assert_eq!(
+            Vec::<i64>::try_from(Element::from(vec![Element::Integer(1), Element::Boolean(false)])),
+            Err(EvaluationError::UnexpectedElement("integer", Element::Boolean(false)))
+        );
+        assert_eq!(
+            bool::try_from(Element::Integer(1)).unwrap_err().to_string(),
+            "expected boolean, found 1: integer"
+        );
+    }
+
+    #[test]
+    fn borrows_strings_as_text() {
+        assert_matches!(Element::new_string("ab").as_string(), Ok(Cow::Borrowed("ab")));
+        assert_matches!(Element::from(vec!['a', 'b']).as_string(), Ok(Cow::Owned(s)) if s == "ab");
+        assert_matches!(Element::Integer(1).as_string(), Err(EvaluationError::NotAString));
+    }
+
     #[test]
     fn parses_float_as_float() {
         assert_matches!(Element::try_from("3.14"), Ok(Element::Float(3.14)));
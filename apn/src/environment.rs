@@ -1,43 +1,443 @@
+pub(crate) mod call;
+mod module;
+
 use crate::element::Element;
+use crate::environment::module::Modules;
+use crate::function::{Function, HostFunction};
 use crate::parser::{parse, ParserError};
-use std::collections::HashMap;
+use crate::permissions::{Capability, Permissions};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::error::Error;
 use std::fmt::{Display, Formatter};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+const MAX_DISPLAYED_FRAMES: usize = 16;
+const COMPACTION_THRESHOLD: usize = 64;
 
 pub struct Environment {
     stack: Vec<Element>,
     variables: HashMap<String, Element>,
     evaluation_history: Vec<EvaluationOperation>,
+    evaluation_depth: usize,
+    undo_history: VecDeque<Vec<EvaluationOperation>>,
+    undo_depth: usize,
+    limits: Limits,
+    call_depth: usize,
+    steps: u64,
+    deadline: Option<Instant>,
+    memory: usize,
+    cancelled: Arc<AtomicBool>,
+    permissions: Permissions,
+    files: Vec<PathBuf>,
+    modules: Modules,
+    output: Box<dyn Write + Send>,
+    input: Box<dyn BufRead + Send>,
+    functions: HashMap<String, Function>,
 }
 
-impl Environment {
-    pub fn new() -> Self {
+/// Bounds on a single top-level `evaluate`; `None` leaves a resource unbounded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Limits {
+    pub max_call_depth: usize,
+    pub max_steps: Option<u64>,
+    pub max_stack_depth: Option<usize>,
+    /// Approximate bytes held by the stack, the variables and the elements an evaluation
+    /// keeps to roll itself back; history kept for `undo` is bounded by the undo depth instead.
+    pub max_memory: Option<usize>,
+    pub timeout: Option<Duration>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
         Self {
+            max_call_depth: 256,
+            max_steps: None,
+            max_stack_depth: None,
+            max_memory: None,
+            timeout: None,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct EnvironmentBuilder {
+    limits: Limits,
+    undo_depth: usize,
+    permissions: Permissions,
+    search_path: Vec<PathBuf>,
+    output: Option<Box<dyn Write + Send>>,
+    input: Option<Box<dyn BufRead + Send>>,
+}
+
+impl EnvironmentBuilder {
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Keeps the operations of the last `depth` successful evaluations so `undo` can revert
+    /// them; off by default, as they hold a copy of every element popped.
+    pub fn undo_depth(mut self, depth: usize) -> Self {
+        self.undo_depth = depth;
+        self
+    }
+
+    pub fn allow(mut self, capability: Capability) -> Self {
+        self.permissions.allow(capability);
+        self
+    }
+
+    pub fn deny(mut self, capability: Capability) -> Self {
+        self.permissions.deny(capability);
+        self
+    }
+
+    /// Revokes every capability; grant the needed ones back with `allow`.
+    pub fn sandboxed(mut self) -> Self {
+        self.permissions = Permissions::none();
+        self
+    }
+
+    /// Confines file access to `root`, which scripts see as `/`.
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.permissions.jail(root);
+        self
+    }
+
+    /// Adds a directory `import` searches after the importing file's own directory.
+    pub fn search_path(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.search_path.push(directory.into());
+        self
+    }
+
+    /// Sends what scripts print to `output` instead of stdout.
+    pub fn output(mut self, output: impl Write + Send + 'static) -> Self {
+        self.output = Some(Box::new(output));
+        self
+    }
+
+    /// Reads script input from `input` instead of stdin.
+    pub fn input(mut self, input: impl BufRead + Send + 'static) -> Self {
+        self.input = Some(Box::new(input));
+        self
+    }
+
+    pub fn build(self) -> Environment {
+        Environment {
             stack: Vec::new(),
             variables: HashMap::new(),
             evaluation_history: Vec::new(),
+            evaluation_depth: 0,
+            undo_history: VecDeque::new(),
+            undo_depth: self.undo_depth,
+            limits: self.limits,
+            call_depth: 0,
+            steps: 0,
+            deadline: None,
+            memory: 0,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            permissions: self.permissions,
+            files: Vec::new(),
+            modules: Modules::new(self.search_path),
+            output: self.output.unwrap_or_else(|| Box::new(io::stdout())),
+            input: self.input.unwrap_or_else(|| Box::new(BufReader::new(io::stdin()))),
+            functions: HashMap::new(),
+        }
+    }
+}
+
+/// The stack and variables of an environment; serializable with the `serde` feature to
+/// checkpoint a session.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    stack: Vec<Element>,
+    variables: HashMap<String, Element>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::with_limits(Limits::default())
+    }
+
+    pub fn with_limits(limits: Limits) -> Self {
+        Self::builder().limits(limits).build()
+    }
+
+    pub fn builder() -> EnvironmentBuilder {
+        EnvironmentBuilder::default()
+    }
+
+    pub(super) fn max_call_depth(&self) -> usize {
+        self.limits.max_call_depth
+    }
+
+    pub(super) fn permissions(&self) -> &Permissions {
+        &self.permissions
+    }
+
+    pub(super) fn output(&mut self) -> Result<&mut (dyn Write + Send), EvaluationError> {
+        self.permissions.require(Capability::Stdout)?;
+        Ok(self.output.as_mut())
+    }
+
+    pub(super) fn input(&mut self) -> Result<&mut (dyn BufRead + Send), EvaluationError> {
+        self.permissions.require(Capability::Stdin)?;
+        Ok(self.input.as_mut())
+    }
+
+    /// Resolves a relative path against the directory of the file being evaluated.
+    pub(super) fn locate(&self, path: &str) -> PathBuf {
+        match self.files.last().and_then(|file| file.parent()) {
+            Some(directory) => directory.join(path),
+            None => PathBuf::from(path),
         }
     }
 
+    /// Evaluates `contents` as the file at `path`: relative paths it reads or imports resolve
+    /// against the file's directory, and errors name the file in their trace.
+    pub fn evaluate_file(
+        &mut self,
+        path: impl Into<PathBuf>,
+        contents: impl Into<String>,
+    ) -> Result<(), EvaluationError> {
+        let path = path.into();
+        let frame = format!("file {}", path.display());
+        self.files.push(path);
+        let result = self.evaluate(contents);
+        self.files.pop();
+        result.map_err(|error| error.traced(frame))
+    }
+
+    /// Makes `function` available to scripts evaluated from now on as the word `name`,
+    /// shadowing any built-in word of the same name.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        function: impl Fn(&mut Environment) -> Result<(), EvaluationError> + Send + Sync + 'static,
+    ) {
+        let name = name.into();
+        let function: Arc<HostFunction> = Arc::new(function);
+        self.functions.insert(name.clone(), Function::Host(name.into(), function));
+    }
+
+    /// Removes a registered word, returning whether it existed. Code parsed earlier keeps it.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.functions.remove(name).is_some()
+    }
+
+    /// Returns a flag that aborts the running evaluation with `Cancelled` once set.
+    /// Evaluations keep failing until the flag is cleared again.
+    pub fn cancellation(&self) -> Arc<AtomicBool> {
+        self.cancelled.clone()
+    }
+
     pub fn evaluate(&mut self, input: impl Into<String>) -> Result<(), EvaluationError> {
-        let elements = parse(input)?;
-        if let Err(e) = self.evaluate_elements(elements) {
-            for item in self.evaluation_history.iter().rev() {
-                match item {
-                    EvaluationOperation::Push => {
-                        self.stack.pop();
-                    }
-                    EvaluationOperation::Pop(element) => {
-                        self.stack.push(element.clone());
-                    }
+        let elements = parse(input, &self.functions)?;
+        self.run(|environment| environment.evaluate_elements(elements))
+    }
+
+    /// Runs `action` as one evaluation: rolled back as a whole if it fails, and undoable
+    /// as a whole if it is not nested in another evaluation.
+    fn run(
+        &mut self,
+        action: impl FnOnce(&mut Environment) -> Result<(), EvaluationError>,
+    ) -> Result<(), EvaluationError> {
+        if self.evaluation_depth == 0 {
+            self.steps = 0;
+            self.deadline = self.limits.timeout.map(|timeout| Instant::now() + timeout);
+        }
+        let mark = self.evaluation_history.len();
+        self.evaluation_depth += 1;
+        let result = self.enter(action);
+        self.evaluation_depth -= 1;
+        if result.is_err() {
+            self.rollback(mark);
+        }
+        if self.evaluation_depth == 0 {
+            let history = std::mem::take(&mut self.evaluation_history);
+            self.memory -= history.iter().map(|operation| self.recorded(operation)).sum::<usize>();
+            if result.is_ok() && !history.is_empty() && self.undo_depth > 0 {
+                if self.undo_history.len() == self.undo_depth {
+                    self.undo_history.pop_front();
+                }
+                self.undo_history.push_back(history);
+            }
+        }
+        result
+    }
+
+    pub fn undo(&mut self) -> bool {
+        if let Some(history) = self.undo_history.pop_back() {
+            self.memory += history.iter().map(|operation| self.recorded(operation)).sum::<usize>();
+            for operation in history.into_iter().rev() {
+                self.revert(operation);
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            stack: self.stack.clone(),
+            variables: self.variables.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.stack = snapshot.stack;
+        self.variables = snapshot.variables;
+        self.memory = self.stack.iter().chain(self.variables.values()).map(|e| self.footprint(e)).sum();
+        self.evaluation_history.clear();
+        self.undo_history.clear();
+    }
+
+    pub(super) fn enter(
+        &mut self,
+        action: impl FnOnce(&mut Environment) -> Result<(), EvaluationError>,
+    ) -> Result<(), EvaluationError> {
+        if self.call_depth >= self.limits.max_call_depth {
+            return Err(EvaluationError::RecursionLimit(self.limits.max_call_depth));
+        }
+        self.call_depth += 1;
+        let result = action(self);
+        self.call_depth -= 1;
+        result
+    }
+
+    pub(super) fn attempt(
+        &mut self,
+        action: impl FnOnce(&mut Environment) -> Result<(), EvaluationError>,
+    ) -> Result<(), EvaluationError> {
+        let mark = self.evaluation_history.len();
+        let result = action(self);
+        if result.is_err() {
+            self.rollback(mark);
+        }
+        result
+    }
+
+    pub(super) fn history_len(&self) -> usize {
+        self.evaluation_history.len()
+    }
+
+    /// Drops operations recorded since `mark` that rolling back to `mark` does not need:
+    /// pushes popped again, and assignments after the first to the same variable. Runs only
+    /// once they have doubled since the last compaction, whose size `retained` keeps.
+    pub(super) fn compact(&mut self, mark: usize, retained: &mut usize) {
+        if self.evaluation_history.len() - mark <= 2 * *retained + COMPACTION_THRESHOLD {
+            return;
+        }
+        let operations = self.evaluation_history.split_off(mark);
+        let mut pushes = 0;
+        let mut assigned = HashSet::new();
+        for operation in operations {
+            match operation {
+                EvaluationOperation::Push => pushes += 1,
+                EvaluationOperation::Pop(element) if pushes > 0 => {
+                    pushes -= 1;
+                    self.memory -= self.footprint(&element);
+                }
+                EvaluationOperation::Assign(name, previous) if assigned.contains(&name) => {
+                    self.memory -= previous.map_or(0, |previous| self.footprint(&previous));
+                }
+                EvaluationOperation::Assign(name, previous) => {
+                    assigned.insert(name.clone());
+                    self.evaluation_history.push(EvaluationOperation::Assign(name, previous));
+                }
+                operation => self.evaluation_history.push(operation),
+            }
+        }
+        self.evaluation_history.extend((0..pushes).map(|_| EvaluationOperation::Push));
+        *retained = self.evaluation_history.len() - mark;
+    }
+
+    fn rollback(&mut self, mark: usize) {
+        while self.evaluation_history.len() > mark {
+            let operation = self.evaluation_history.pop().unwrap();
+            self.revert(operation);
+        }
+    }
+
+    fn revert(&mut self, operation: EvaluationOperation) {
+        match operation {
+            EvaluationOperation::Push => {
+                if let Some(element) = self.stack.pop() {
+                    self.memory -= self.footprint(&element);
+                }
+            }
+            EvaluationOperation::Pop(element) => self.stack.push(element),
+            EvaluationOperation::Assign(name, Some(previous)) => {
+                if let Some(current) = self.variables.insert(name, previous) {
+                    self.memory -= self.footprint(&current);
+                }
+            }
+            EvaluationOperation::Assign(name, None) => {
+                if let Some(current) = self.variables.remove(&name) {
+                    self.memory -= self.footprint(&current);
                 }
             }
-            self.evaluation_history.clear();
-            Err(e)
+        }
+    }
+
+    /// Bytes held by an operation's copy of an element, which moves back into the stack or
+    /// the variables when the operation is reverted.
+    fn recorded(&self, operation: &EvaluationOperation) -> usize {
+        match operation {
+            EvaluationOperation::Pop(element) | EvaluationOperation::Assign(_, Some(element)) => {
+                self.footprint(element)
+            }
+            _ => 0,
+        }
+    }
+
+    fn footprint(&self, element: &Element) -> usize {
+        if self.limits.max_memory.is_some() {
+            element.size()
         } else {
-            self.evaluation_history.clear();
-            Ok(())
+            0
+        }
+    }
+
+    /// Fails if `bytes` more would exceed the memory limit; words building large elements
+    /// check this before allocating them.
+    pub(super) fn reserve(&self, bytes: usize) -> Result<(), EvaluationError> {
+        if let Some(max) = self.limits.max_memory
+            && self.memory.saturating_add(bytes) > max
+        {
+            return Err(EvaluationError::MemoryLimit(max));
+        }
+        Ok(())
+    }
+
+    fn allocate(&mut self, bytes: usize) -> Result<(), EvaluationError> {
+        self.reserve(bytes)?;
+        self.memory += bytes;
+        Ok(())
+    }
+
+    fn step(&mut self) -> Result<(), EvaluationError> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(EvaluationError::Cancelled);
+        }
+        self.steps += 1;
+        if let Some(max) = self.limits.max_steps
+            && self.steps > max
+        {
+            return Err(EvaluationError::StepLimit(max));
         }
+        if let (Some(deadline), Some(timeout)) = (self.deadline, self.limits.timeout)
+            && Instant::now() >= deadline
+        {
+            return Err(EvaluationError::Timeout(timeout));
+        }
+        Ok(())
     }
 
     fn evaluate_elements(&mut self, elements: Vec<Element>) -> Result<(), EvaluationError> {
@@ -47,49 +447,131 @@ impl Environment {
         Ok(())
     }
 
-    pub(super) fn push(&mut self, element: Element) -> Result<(), EvaluationError> {
+    /// Pushes an element onto the stack, applying it first if it is a function. Applying a
+    /// function from outside an evaluation runs it as one.
+    pub fn push(&mut self, element: impl Into<Element>) -> Result<(), EvaluationError> {
+        let element = element.into();
+        if self.evaluation_depth > 0 {
+            self.step()?;
+        } else if let Element::Function(_) = element {
+            return self.run(|environment| environment.push(element));
+        }
         match element {
-            Element::Function(f) => f.execute(self)?,
-            _ => {
-                self.evaluation_history.push(EvaluationOperation::Push);
+            Element::Function(f) => {
+                let mark = self.evaluation_history.len();
+                f.execute(self).map_err(|e| self.describe(e, &f, mark))?
+            }
+            element => {
+                if let Some(max) = self.limits.max_stack_depth
+                    && self.stack.len() >= max
+                {
+                    return Err(EvaluationError::StackLimit(max));
+                }
+                self.allocate(self.footprint(&element))?;
+                self.record(EvaluationOperation::Push);
                 self.stack.push(element);
             }
         }
         Ok(())
     }
 
-    pub(super) fn pop(&mut self) -> Result<Element, EvaluationError> {
+    fn describe(&self, error: EvaluationError, function: &Function, mark: usize) -> EvaluationError {
+        match error {
+            EvaluationError::FunctionNotApplicable(None) => {
+                EvaluationError::FunctionNotApplicable(Some(self.mismatch(function, mark)))
+            }
+            EvaluationError::InvalidStackElements(None) => {
+                EvaluationError::InvalidStackElements(Some(self.mismatch(function, mark)))
+            }
+            error => error,
+        }
+    }
+
+    fn mismatch(&self, function: &Function, mark: usize) -> Mismatch {
+        Mismatch {
+            function: function.name().to_string(),
+            expected: function.signature(),
+            found: self.evaluation_history[mark..]
+                .iter()
+                .rev()
+                .filter_map(|operation| match operation {
+                    EvaluationOperation::Pop(element) => Some(element.clone()),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Pops the top element as is, leaving variables unresolved.
+    pub fn pop(&mut self) -> Result<Element, EvaluationError> {
         if self.stack.is_empty() {
             Err(EvaluationError::EmptyStack)
         } else {
             let top = self.stack.pop().unwrap();
-            self.evaluation_history
-                .push(EvaluationOperation::Pop(top.clone()));
+            if self.evaluation_depth > 0 {
+                self.evaluation_history.push(EvaluationOperation::Pop(top.clone()));
+            } else {
+                self.memory -= self.footprint(&top);
+                self.undo_history.clear();
+            }
             Ok(top)
         }
     }
 
-    pub(super) fn pop_value(&mut self) -> Result<Element, EvaluationError> {
+    /// Pops the top element with the variables it refers to replaced by their values.
+    pub fn pop_value(&mut self) -> Result<Element, EvaluationError> {
         let top = self.pop()?;
         self.resolve(&top)
     }
 
-    fn resolve(&self, element: &Element) -> Result<Element, EvaluationError> {
+    pub fn peek(&self) -> Result<&Element, EvaluationError> {
+        self.stack.last().ok_or(EvaluationError::EmptyStack)
+    }
+
+    pub(super) fn pop_string(&mut self) -> Result<String, EvaluationError> {
+        self.pop_value()?.into_string()
+    }
+
+    pub(super) fn resolve(&self, element: &Element) -> Result<Element, EvaluationError> {
+        self.resolve_within(element, &mut Vec::new())
+    }
+
+    fn resolve_within(
+        &self,
+        element: &Element,
+        resolving: &mut Vec<String>,
+    ) -> Result<Element, EvaluationError> {
         match element {
-            Element::Variable(name) => self.resolve_variable(name),
+            Element::Variable(name) => self.resolve_variable(name, resolving),
             Element::Array(array) => Ok(Element::Array(
                 array
                     .iter()
-                    .map(|e| self.resolve(&e))
+                    .map(|e| self.resolve_within(e, resolving))
                     .collect::<Result<Vec<Element>, EvaluationError>>()?,
             )),
+            Element::Map(entries) => Ok(Element::Map(
+                entries
+                    .iter()
+                    .map(|(k, v)| Ok((k.clone(), self.resolve_within(v, resolving)?)))
+                    .collect::<Result<_, EvaluationError>>()?,
+            )),
             element => Ok(element.clone()),
         }
     }
 
-    fn resolve_variable(&self, name: &String) -> Result<Element, EvaluationError> {
+    fn resolve_variable(
+        &self,
+        name: &String,
+        resolving: &mut Vec<String>,
+    ) -> Result<Element, EvaluationError> {
+        if resolving.contains(name) {
+            return Err(EvaluationError::CircularVariableReference);
+        }
         if let Some(element) = self.variables.get(name) {
-            self.resolve(element)
+            resolving.push(name.clone());
+            let result = self.resolve_within(element, resolving);
+            resolving.pop();
+            result
         } else {
             Err(EvaluationError::UndefinedVariable(name.clone()))
         }
@@ -100,10 +582,24 @@ impl Environment {
         variable: String,
         value: Element,
     ) -> Result<(), EvaluationError> {
-        self.variables.insert(variable, value);
+        self.allocate(self.footprint(&value))?;
+        let previous = self.variables.insert(variable.clone(), value);
+        self.record(EvaluationOperation::Assign(variable, previous));
         Ok(())
     }
 
+    /// Keeps an operation for rollback and undo while evaluating; what host code does between
+    /// evaluations is not part of any of them, and since undo reverts operations by position,
+    /// it forgets the evaluations the host has changed the state after.
+    fn record(&mut self, operation: EvaluationOperation) {
+        if self.evaluation_depth > 0 {
+            self.evaluation_history.push(operation);
+        } else {
+            self.memory -= self.recorded(&operation);
+            self.undo_history.clear();
+        }
+    }
+
     pub fn stack_len(&self) -> usize {
         self.stack.len()
     }
@@ -111,11 +607,24 @@ impl Environment {
     pub fn stack(&self) -> impl Iterator<Item = &Element> {
         self.stack.iter()
     }
+
+    pub fn vars(&self) -> impl Iterator<Item = (&String, &Element)> {
+        self.variables.iter()
+    }
+
+    pub fn get_var(&self, name: &str) -> Option<&Element> {
+        self.variables.get(name)
+    }
+
+    pub fn set_var(&mut self, name: impl Into<String>, value: impl Into<Element>) -> Result<(), EvaluationError> {
+        self.assign(name.into(), value.into())
+    }
 }
 
 enum EvaluationOperation {
     Push,
     Pop(Element),
+    Assign(String, Option<Element>),
 }
 
 #[derive(Debug, PartialEq)]
@@ -123,18 +632,170 @@ pub enum EvaluationError {
     Parser(ParserError),
     EmptyStack,
     DivisionByZero,
-    FunctionNotApplicable,
+    /// Carries `None` until the environment describes the word that failed.
+    FunctionNotApplicable(Option<Mismatch>),
     UndefinedVariable(String),
-    InvalidStackElements,
+    InvalidStackElements(Option<Mismatch>),
     CircularVariableReference,
     NotAString,
     NotACharacter,
+    NotANumber(String),
+    NotAKey,
+    KeyNotFound(String),
     IO(String),
+    Thrown(Element),
+    AssertionFailed(Vec<Element>, Vec<Element>),
+    RecursionLimit(usize),
+    StepLimit(u64),
+    StackLimit(usize),
+    MemoryLimit(usize),
+    Timeout(Duration),
+    Cancelled,
+    PermissionDenied(String),
+    ModuleNotFound(String),
+    CircularImport(String),
+    UnexpectedElement(&'static str, Element),
+    ResultCount(usize, usize),
+    Json(String),
+    Traced(Box<EvaluationError>, Vec<String>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Mismatch {
+    pub function: String,
+    pub expected: &'static str,
+    pub found: Vec<Element>,
+}
+
+impl EvaluationError {
+    pub fn root(&self) -> &EvaluationError {
+        match self {
+            EvaluationError::Traced(error, _) => error.root(),
+            error => error,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self.root() {
+            EvaluationError::Parser(_) => "parser",
+            EvaluationError::EmptyStack => "empty_stack",
+            EvaluationError::DivisionByZero => "division_by_zero",
+            EvaluationError::FunctionNotApplicable(_) => "function_not_applicable",
+            EvaluationError::UndefinedVariable(_) => "undefined_variable",
+            EvaluationError::InvalidStackElements(_) => "invalid_stack_elements",
+            EvaluationError::CircularVariableReference => "circular_variable_reference",
+            EvaluationError::NotAString => "not_a_string",
+            EvaluationError::NotACharacter => "not_a_character",
+            EvaluationError::NotANumber(_) => "not_a_number",
+            EvaluationError::NotAKey => "not_a_key",
+            EvaluationError::KeyNotFound(_) => "key_not_found",
+            EvaluationError::IO(_) => "io",
+            EvaluationError::Thrown(_) => "thrown",
+            EvaluationError::AssertionFailed(_, _) => "assertion_failed",
+            EvaluationError::RecursionLimit(_) => "recursion_limit",
+            EvaluationError::StepLimit(_) => "step_limit",
+            EvaluationError::StackLimit(_) => "stack_limit",
+            EvaluationError::MemoryLimit(_) => "memory_limit",
+            EvaluationError::Timeout(_) => "timeout",
+            EvaluationError::Cancelled => "cancelled",
+            EvaluationError::PermissionDenied(_) => "permission_denied",
+            EvaluationError::ModuleNotFound(_) => "module_not_found",
+            EvaluationError::CircularImport(_) => "circular_import",
+            EvaluationError::UnexpectedElement(_, _) => "unexpected_element",
+            EvaluationError::ResultCount(_, _) => "result_count",
+            EvaluationError::Json(_) => "json",
+            EvaluationError::Traced(error, _) => error.kind(),
+        }
+    }
+
+    pub fn trace(&self) -> &[String] {
+        match self {
+            EvaluationError::Traced(_, trace) => trace,
+            _ => &[],
+        }
+    }
+
+    pub(crate) fn traced(self, frame: String) -> EvaluationError {
+        match self {
+            EvaluationError::Traced(error, mut trace) => {
+                trace.push(frame);
+                EvaluationError::Traced(error, trace)
+            }
+            error => EvaluationError::Traced(Box::new(error), vec![frame]),
+        }
+    }
 }
 
 impl Display for EvaluationError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("{:?}", self))
+        match self {
+            EvaluationError::Parser(e) => write!(f, "parse error: {}", e),
+            EvaluationError::EmptyStack => f.write_str("stack is empty"),
+            EvaluationError::DivisionByZero => f.write_str("division by zero"),
+            EvaluationError::FunctionNotApplicable(Some(m)) => write!(f, "function not applicable: {}", m),
+            EvaluationError::FunctionNotApplicable(None) => f.write_str("function not applicable"),
+            EvaluationError::UndefinedVariable(name) => write!(f, "undefined variable ${}", name),
+            EvaluationError::InvalidStackElements(Some(m)) => write!(f, "invalid stack elements: {}", m),
+            EvaluationError::InvalidStackElements(None) => f.write_str("invalid stack elements"),
+            EvaluationError::CircularVariableReference => f.write_str("circular variable reference"),
+            EvaluationError::NotAString => f.write_str("expected a string"),
+            EvaluationError::NotACharacter => f.write_str("expected a character"),
+            EvaluationError::NotANumber(s) => write!(f, "\"{}\" is not a number", s),
+            EvaluationError::NotAKey => f.write_str("expected a boolean, integer, char, string or symbol as map key"),
+            EvaluationError::KeyNotFound(key) => write!(f, "key {} not found", key),
+            EvaluationError::IO(message) => write!(f, "I/O error: {}", message),
+            EvaluationError::Thrown(value) => write!(f, "uncaught error: {}", value.to_text()),
+            EvaluationError::RecursionLimit(depth) => write!(f, "maximum call depth of {} exceeded", depth),
+            EvaluationError::StepLimit(steps) => write!(f, "maximum of {} evaluation steps exceeded", steps),
+            EvaluationError::StackLimit(depth) => write!(f, "maximum stack depth of {} exceeded", depth),
+            EvaluationError::MemoryLimit(bytes) => write!(f, "memory limit of {} bytes exceeded", bytes),
+            EvaluationError::Timeout(timeout) => write!(f, "evaluation timed out after {:?}", timeout),
+            EvaluationError::Cancelled => f.write_str("evaluation cancelled"),
+            EvaluationError::PermissionDenied(what) => write!(f, "permission denied: {}", what),
+            EvaluationError::ModuleNotFound(name) => write!(f, "module {} not found", name),
+            EvaluationError::CircularImport(name) => write!(f, "module {} imports itself", name),
+            EvaluationError::ResultCount(expected, found) => {
+                write!(f, "expected {} results, found {}", expected, found)
+            }
+            EvaluationError::Json(message) => write!(f, "JSON error: {}", message),
+            EvaluationError::UnexpectedElement(expected, found) => {
+                write!(f, "expected {}, found {}: {}", expected, found, found.type_name())
+            }
+            EvaluationError::AssertionFailed(expected, actual) => {
+                write!(f, "assertion failed\n  expected: {}\n  actual:   {}",
+                       Element::Array(expected.clone()), Element::Array(actual.clone()))?;
+                for i in 0..expected.len().max(actual.len()) {
+                    match (expected.get(i), actual.get(i)) {
+                        (Some(e), Some(a)) if e == a => {}
+                        (Some(e), Some(a)) => write!(f, "\n  at {}: expected {}, found {}", i, e, a)?,
+                        (Some(e), None) => write!(f, "\n  at {}: expected {}, found nothing", i, e)?,
+                        (None, Some(a)) => write!(f, "\n  at {}: expected nothing, found {}", i, a)?,
+                        (None, None) => {}
+                    }
+                }
+                Ok(())
+            }
+            EvaluationError::Traced(error, trace) => {
+                write!(f, "{}", error)?;
+                for frame in trace.iter().take(MAX_DISPLAYED_FRAMES) {
+                    write!(f, "\n  in {}", frame)?;
+                }
+                if trace.len() > MAX_DISPLAYED_FRAMES {
+                    write!(f, "\n  ... {} more", trace.len() - MAX_DISPLAYED_FRAMES)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` expects ( {} ) but found (", self.function, self.expected)?;
+        for element in &self.found {
+            write!(f, " {}: {}", element, element.type_name())?;
+        }
+        f.write_str(" )")
     }
 }
 
@@ -175,6 +836,337 @@ mod tests {
         );
     }
 
+    #[test]
+    fn an_error_during_evaluation_does_not_change_variables() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("1 $x ="), Ok(()));
+        assert_matches!(env.evaluate("2 $x = 3 $y = 4 $x = +"), Err(EvaluationError::EmptyStack));
+        assert_eq!(env.variables.get("x"), Some(&Element::Integer(1)));
+        assert_eq!(env.variables.get("y"), None);
+    }
+
+    #[test]
+    fn an_error_after_nested_evaluation_rolls_back_everything() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("1 \"../programs/inc.apn\" read $inc . +"), Err(EvaluationError::EmptyStack));
+        assert_eq!(env.stack, vec![]);
+        assert_eq!(env.variables.get("inc"), None);
+    }
+
+    #[test]
+    fn undoes_last_successful_evaluations() {
+        let mut env = Environment::builder().undo_depth(2).build();
+        assert_matches!(env.evaluate("1 $x ="), Ok(()));
+        assert_matches!(env.evaluate("2 $x = 3 4"), Ok(()));
+        assert_matches!(env.evaluate("+ + +"), Err(EvaluationError::EmptyStack));
+        assert!(env.undo());
+        assert_eq!(env.stack, vec![]);
+        assert_eq!(env.variables.get("x"), Some(&Element::Integer(1)));
+        assert!(env.undo());
+        assert_eq!(env.variables.get("x"), None);
+        assert!(!env.undo());
+    }
+
+    #[test]
+    fn keeps_only_undo_depth_evaluations() {
+        let mut env = Environment::builder().undo_depth(1).build();
+        assert_matches!(env.evaluate("1"), Ok(()));
+        assert_matches!(env.evaluate("2"), Ok(()));
+        assert!(env.undo());
+        assert!(!env.undo());
+        assert_eq!(env.stack, vec![Element::Integer(1)]);
+    }
+
+    #[test]
+    fn drops_history_when_undo_is_disabled() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("1 2 + 3 $x ="), Ok(()));
+        assert!(env.evaluation_history.is_empty());
+        assert!(env.undo_history.is_empty());
+        assert!(!env.undo());
+        assert_eq!(env.stack, vec![Element::Integer(3)]);
+    }
+
+    #[test]
+    fn restores_snapshot() {
+        let mut env = Environment::builder().undo_depth(1).build();
+        assert_matches!(env.evaluate("1 $x = 2"), Ok(()));
+        let snapshot = env.snapshot();
+        assert_matches!(env.evaluate("clear 3 $x = 4 $y ="), Ok(()));
+        env.restore(snapshot.clone());
+        assert_eq!(env.snapshot(), snapshot);
+        assert_eq!(env.stack, vec![Element::Integer(2)]);
+        assert_eq!(env.variables.get("x"), Some(&Element::Integer(1)));
+        assert_eq!(env.variables.get("y"), None);
+        assert!(!env.undo());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn restores_serialized_snapshot() {
+        let mut env = Environment::new();
+        let program = "{ 1 + } $inc = #{ :a [1.5 'c'] 2 \"two\" } $m = $inc :sym true";
+        assert_matches!(env.evaluate(program), Ok(()));
+        let json = serde_json::to_string(&env.snapshot()).unwrap();
+        let mut restored = Environment::new();
+        restored.restore(serde_json::from_str(&json).unwrap());
+        assert_eq!(restored.snapshot(), env.snapshot());
+        assert_matches!(restored.evaluate("pop pop 1 $inc ."), Ok(()));
+        assert_matches!(restored.pop(), Ok(Element::Integer(2)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn errs_on_serialized_unknown_function() {
+        let result = serde_json::from_str::<Element>(r#"{"Function":"no_such_word"}"#);
+        assert!(result.unwrap_err().to_string().starts_with("unknown function no_such_word"));
+        assert_eq!(serde_json::from_str::<Element>(r#"{"Function":"swap"}"#).unwrap(), Element::Function(Function::try_from("rotate").unwrap()));
+    }
+
+    #[test]
+    fn describes_failing_function() {
+        let mut env = Environment::new();
+        let error = env.evaluate("1 \"a\" +").unwrap_err();
+        assert_eq!(
+            error,
+            EvaluationError::FunctionNotApplicable(Some(Mismatch {
+                function: "+".to_string(),
+                expected: "number number -- number",
+                found: vec![Element::Integer(1), Element::String("a".to_string())],
+            }))
+        );
+        assert_eq!(
+            error.to_string(),
+            "function not applicable: `+` expects ( number number -- number ) but found ( 1: integer \"a\": string )"
+        );
+    }
+
+    #[test]
+    fn traces_enclosing_procedures_and_variables() {
+        let mut env = Environment::new();
+        let error = env.evaluate("{ 1 true + } $inner = { $inner . pop } $outer = $outer .").unwrap_err();
+        assert_matches!(error.root(), EvaluationError::FunctionNotApplicable(Some(m)) if m.function == "+");
+        assert_eq!(error.trace(), &["$inner".to_string(), "$outer".to_string()]);
+        assert!(error.to_string().ends_with("\n  in $inner\n  in $outer"));
+    }
+
+    #[test]
+    fn errs_on_circular_variable_reference() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("$x $x = [$y] $y ="), Ok(()));
+        assert_matches!(env.evaluate("$x pop"), Err(EvaluationError::CircularVariableReference));
+        assert_matches!(env.evaluate("$y pop"), Err(EvaluationError::CircularVariableReference));
+    }
+
+    #[test]
+    fn errs_when_exceeding_call_depth() {
+        let mut env = Environment::with_limits(Limits { max_call_depth: 50, ..Limits::default() });
+        let error = env.evaluate("{ $f . pop } $f = $f .").unwrap_err();
+        assert_matches!(error.root(), EvaluationError::RecursionLimit(50));
+        assert_eq!(error.trace().len(), 49);
+        assert_matches!(env.evaluate("{ 1 - copy 0 > { $g . } {} if . } $g = 20 $g ."), Ok(()));
+    }
+
+    #[test]
+    fn exchanges_values_with_rust() -> Result<(), EvaluationError> {
+        let mut env = Environment::new();
+        assert_matches!(env.set_var("xs", vec![1, 2, 3]), Ok(()));
+        assert_matches!(env.push(10), Ok(()));
+        assert_matches!(env.evaluate("{ + } $add = $xs { 2 * } map"), Ok(()));
+        assert_eq!(env.peek(), Ok(&Element::from(vec![2, 4, 6])));
+        assert_eq!(Vec::<i64>::try_from(env.pop()?), Ok(vec![2, 4, 6]));
+        let add = env.get_var("add").cloned().unwrap();
+        env.push(5)?;
+        env.push(add)?;
+        env.push(Element::Function(Function::try_from(".").unwrap()))?;
+        assert_eq!(i64::try_from(env.pop_value()?), Ok(15));
+        assert_matches!(env.peek(), Err(EvaluationError::EmptyStack));
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_host_operations_out_of_history() -> Result<(), EvaluationError> {
+        let mut env = Environment::builder().undo_depth(4).build();
+        env.set_var("x", 1)?;
+        env.push(1)?;
+        env.push(2)?;
+        env.pop()?;
+        assert!(env.evaluation_history.is_empty());
+        env.evaluate("3")?;
+        assert!(env.undo());
+        assert!(!env.undo());
+        assert_eq!(env.get_var("x"), Some(&Element::Integer(1)));
+        assert_eq!(env.stack, vec![Element::Integer(1)]);
+        env.push(Element::Function(Function::try_from("pop").unwrap()))?;
+        assert!(env.stack.is_empty());
+        assert_matches!(env.push(Element::Function(Function::try_from("+").unwrap())), Err(EvaluationError::EmptyStack));
+        Ok(())
+    }
+
+    #[test]
+    fn forgets_undo_history_after_host_changes() -> Result<(), EvaluationError> {
+        let mut env = Environment::builder().undo_depth(4).build();
+        env.evaluate("1 2")?;
+        env.push(99)?;
+        assert!(!env.undo());
+        assert_eq!(env.stack, vec![Element::Integer(1), Element::Integer(2), Element::Integer(99)]);
+        env.evaluate("1 $x =")?;
+        env.set_var("x", 5)?;
+        assert!(!env.undo());
+        assert_eq!(env.get_var("x"), Some(&Element::Integer(5)));
+        env.evaluate("3")?;
+        env.pop()?;
+        assert!(!env.undo());
+        assert_eq!(env.stack.len(), 3);
+        env.evaluate("4")?;
+        assert!(env.undo());
+        assert_eq!(env.stack.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn evaluates_registered_host_functions() {
+        let mut env = Environment::new();
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        env.register("next_id", {
+            let calls = calls.clone();
+            move |environment| {
+                let id = calls.fetch_add(1, Ordering::Relaxed);
+                environment.push(Element::Integer(id as i64))
+            }
+        });
+        assert_matches!(env.evaluate("next_id next_id { next_id } ."), Ok(()));
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+        assert_matches!(env.pop(), Ok(Element::Integer(2)));
+        assert_matches!(env.pop(), Ok(Element::Integer(1)));
+    }
+
+    #[test]
+    fn host_functions_shadow_builtins_until_unregistered() {
+        let mut env = Environment::new();
+        env.register("+", |environment| {
+            let a = environment.pop_string()?;
+            let b = environment.pop_string()?;
+            environment.push(Element::String(b + &a))
+        });
+        assert_matches!(env.evaluate("\"a\" \"b\" + { 1 2 + } $f ="), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::String(s)) if s == "ab");
+        let error = env.evaluate("1 2 +").unwrap_err();
+        assert_matches!(error, EvaluationError::NotAString);
+        assert!(env.unregister("+"));
+        assert!(!env.unregister("+"));
+        assert_matches!(env.evaluate("1 2 +"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(3)));
+        assert_matches!(env.evaluate("$f ."), Err(EvaluationError::Traced(_, _)));
+    }
+
+    #[test]
+    fn describes_failing_host_function_by_name() {
+        let mut env = Environment::new();
+        env.register("fail", |environment| {
+            environment.pop()?;
+            Err(EvaluationError::InvalidStackElements(None))
+        });
+        let error = env.evaluate("1 fail").unwrap_err();
+        assert_matches!(error, EvaluationError::InvalidStackElements(Some(m)) if m.function == "fail" && m.found == vec![Element::Integer(1)]);
+    }
+
+    #[test]
+    fn default_call_depth_does_not_overflow_native_stack() {
+        let mut env = Environment::new();
+        let error = env.evaluate("{ 1 $f . + } $f = $f .").unwrap_err();
+        assert_matches!(error.root(), EvaluationError::RecursionLimit(_));
+        assert_eq!(env.stack_len(), 0);
+    }
+
+    #[test]
+    fn errs_when_exceeding_step_limit() {
+        let mut env = Environment::with_limits(Limits { max_steps: Some(100), ..Limits::default() });
+        let error = env.evaluate("1 {copy} 1000000000 repeat_eval").unwrap_err();
+        assert_matches!(error.root(), EvaluationError::StepLimit(100));
+        assert_eq!(env.stack_len(), 0);
+        assert_matches!(env.evaluate("1 2 +"), Ok(()));
+    }
+
+    #[test]
+    fn errs_when_exceeding_stack_depth() {
+        let mut env = Environment::with_limits(Limits { max_stack_depth: Some(3), ..Limits::default() });
+        assert_matches!(env.evaluate("1 2 3"), Ok(()));
+        assert_matches!(env.evaluate("+ 4 5"), Err(EvaluationError::StackLimit(3)));
+        assert_eq!(env.stack_len(), 3);
+    }
+
+    #[test]
+    fn errs_when_exceeding_memory_limit() {
+        let mut env = Environment::with_limits(Limits { max_memory: Some(1024), ..Limits::default() });
+        assert_matches!(env.evaluate("[1 2 3] $x = 1 2 3"), Ok(()));
+        let error = env.evaluate("1 {copy} 1000 repeat_eval").unwrap_err();
+        assert_matches!(error.root(), EvaluationError::MemoryLimit(1024));
+        assert_matches!(env.evaluate("pop pop pop \"a long enough string\" 100 repeat"), Err(EvaluationError::MemoryLimit(_)));
+        assert_matches!(env.evaluate("pop pop pop"), Ok(()));
+        assert_eq!(env.memory, Element::Array(vec![Element::Integer(1); 3]).size());
+    }
+
+    #[test]
+    fn errs_before_building_elements_beyond_memory_limit() {
+        let mut env = Environment::with_limits(Limits { max_memory: Some(1024), ..Limits::default() });
+        assert_matches!(env.evaluate("0 20000000000 range"), Err(EvaluationError::MemoryLimit(1024)));
+        assert_matches!(env.evaluate("1 1000000000000 repeat"), Err(EvaluationError::MemoryLimit(1024)));
+        assert_matches!(env.evaluate("1 20 range copy ++ copy ++"), Err(EvaluationError::MemoryLimit(1024)));
+        assert_eq!(env.memory, 0);
+    }
+
+    #[test]
+    fn counts_history_against_memory_limit() {
+        let mut env = Environment::with_limits(Limits { max_memory: Some(1024), ..Limits::default() });
+        let error = env.evaluate(format!("[1 2 3]{}", " copy pop".repeat(8))).unwrap_err();
+        assert_matches!(error, EvaluationError::MemoryLimit(1024));
+        assert_eq!(env.memory, 0);
+        assert_matches!(env.evaluate("[1 2 3] copy pop $x ="), Ok(()));
+        assert_matches!(env.evaluate("[4] $x ="), Ok(()));
+        assert_eq!(env.memory, Element::from(vec![4]).size());
+        let limits = Limits { max_memory: Some(1024), ..Limits::default() };
+        let mut env = Environment::builder().limits(limits).undo_depth(1).build();
+        assert_matches!(env.evaluate("1 2 3 pop 4 $x = 5 $x ="), Ok(()));
+        assert!(env.undo());
+        assert_eq!(env.memory, 0);
+    }
+
+    #[test]
+    fn compacts_history_of_loops_within_memory_limit() {
+        let mut env = Environment::with_limits(Limits { max_memory: Some(1 << 16), ..Limits::default() });
+        assert_matches!(env.evaluate("[1 2 3 4 5 6 7 8 9 10] { copy pop } 30000 repeat_eval pop"), Ok(()));
+        assert_eq!(env.memory, 0);
+    }
+
+    #[test]
+    fn errs_when_exceeding_timeout() {
+        let timeout = Duration::from_millis(10);
+        let mut env = Environment::with_limits(Limits { timeout: Some(timeout), ..Limits::default() });
+        let error = env.evaluate("{ $f . } $f = $f .").unwrap_err();
+        assert_eq!(error.root(), &EvaluationError::Timeout(timeout));
+        assert_matches!(env.evaluate("1 2 +"), Ok(()));
+    }
+
+    #[test]
+    fn errs_when_cancelled_from_another_thread() {
+        let mut env = Environment::new();
+        let cancellation = env.cancellation();
+        let canceller = std::thread::spawn({
+            let cancellation = cancellation.clone();
+            move || {
+                std::thread::sleep(Duration::from_millis(10));
+                cancellation.store(true, Ordering::Relaxed);
+            }
+        });
+        let error = env.evaluate("{ $f . } $f = $f .").unwrap_err();
+        canceller.join().unwrap();
+        assert_eq!(error.root(), &EvaluationError::Cancelled);
+        assert_matches!(env.evaluate("1"), Err(EvaluationError::Cancelled));
+        cancellation.store(false, Ordering::Relaxed);
+        assert_matches!(env.evaluate("1"), Ok(()));
+    }
+
     #[test]
     fn stores_array_as_variable() {
         let mut env = Environment::new();
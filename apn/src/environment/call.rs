@@ -0,0 +1,141 @@
+use super::{Environment, EvaluationError};
+use crate::element::Element;
+use crate::function::Function;
+
+/// Values pushed, in order, as the arguments of a procedure called from Rust.
+pub trait Arguments {
+    fn push_onto(self, environment: &mut Environment) -> Result<(), EvaluationError>;
+}
+
+/// Values a procedure called from Rust is expected to leave on the stack.
+pub trait Results: Sized {
+    const COUNT: usize;
+
+    fn pop_from(environment: &mut Environment) -> Result<Self, EvaluationError>;
+}
+
+macro_rules! arguments {
+    ($($name: ident),*) => {
+        impl<$($name: Into<Element>),*> Arguments for ($($name,)*) {
+            #[allow(non_snake_case, unused_variables)]
+            fn push_onto(self, environment: &mut Environment) -> Result<(), EvaluationError> {
+                let ($($name,)*) = self;
+                $(environment.push($name)?;)*
+                Ok(())
+            }
+        }
+    };
+}
+
+arguments!();
+arguments!(A);
+arguments!(A, B);
+arguments!(A, B, C);
+arguments!(A, B, C, D);
+arguments!(A, B, C, D, E);
+arguments!(A, B, C, D, E, F);
+
+macro_rules! single_result {
+    ($($type: ty),*) => {
+        $(impl Results for $type {
+            const COUNT: usize = 1;
+
+            fn pop_from(environment: &mut Environment) -> Result<Self, EvaluationError> {
+                Self::try_from(environment.pop_value()?)
+            }
+        })*
+    };
+}
+
+single_result!(i64, f64, bool, char, String);
+
+impl Results for Element {
+    const COUNT: usize = 1;
+
+    fn pop_from(environment: &mut Environment) -> Result<Self, EvaluationError> {
+        environment.pop_value()
+    }
+}
+
+impl<T: TryFrom<Element, Error = EvaluationError>> Results for Vec<T> {
+    const COUNT: usize = 1;
+
+    fn pop_from(environment: &mut Environment) -> Result<Self, EvaluationError> {
+        Self::try_from(environment.pop_value()?)
+    }
+}
+
+macro_rules! tuple_results {
+    (@pop $environment: ident;) => {};
+    (@pop $environment: ident; $first: ident $(, $rest: ident)*) => {
+        tuple_results!(@pop $environment; $($rest),*);
+        let $first = $first::pop_from($environment)?;
+    };
+    ($count: literal; $($name: ident),*) => {
+        impl<$($name: Results),*> Results for ($($name,)*) {
+            const COUNT: usize = $count;
+
+            #[allow(non_snake_case, unused_variables)]
+            fn pop_from(environment: &mut Environment) -> Result<Self, EvaluationError> {
+                tuple_results!(@pop environment; $($name),*);
+                Ok(($($name,)*))
+            }
+        }
+    };
+}
+
+tuple_results!(0;);
+tuple_results!(2; A, B);
+tuple_results!(3; A, B, C);
+tuple_results!(4; A, B, C, D);
+
+impl Environment {
+    /// Evaluates the procedure stored in variable `name` with `arguments` on the stack and
+    /// returns what it leaves there; fails and rolls back unless it leaves exactly
+    /// `R::COUNT` elements.
+    pub fn call<R: Results>(&mut self, name: &str, arguments: impl Arguments) -> Result<R, EvaluationError> {
+        let mut results = None;
+        self.run(|environment| {
+            let base = environment.stack_len();
+            arguments.push_onto(environment)?;
+            environment.push(Element::Variable(name.to_string()))?;
+            environment.push(Element::Function(Function::try_from(".").unwrap()))?;
+            let count = environment.stack_len().saturating_sub(base);
+            if count != R::COUNT {
+                return Err(EvaluationError::ResultCount(R::COUNT, count));
+            }
+            results = Some(R::pop_from(environment)?);
+            Ok(())
+        })?;
+        Ok(results.unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches::assert_matches;
+
+    #[test]
+    fn calls_procedure_with_arguments() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("\"../programs/fib.apn\" read"), Ok(()));
+        assert_eq!(env.call::<i64>("fib", (10,)), Ok(55));
+        assert_matches!(env.evaluate("{ copy 2 % swap 2 / } $divmod = { pop } $drop = { \"hi\" } $greet ="), Ok(()));
+        assert_eq!(env.call::<(i64, f64)>("divmod", (7,)), Ok((1, 3.5)));
+        assert_eq!(env.call::<()>("drop", ('x',)), Ok(()));
+        assert_eq!(env.call::<String>("greet", ()), Ok("hi".to_string()));
+        assert_eq!(env.stack_len(), 0);
+    }
+
+    #[test]
+    fn rolls_back_on_unexpected_results() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("0 { copy } $dup = { pop pop } $drop2 = { 1 } $one ="), Ok(()));
+        assert_eq!(env.call::<i64>("dup", (1,)), Err(EvaluationError::ResultCount(1, 2)));
+        assert_eq!(env.call::<i64>("drop2", (1,)), Err(EvaluationError::ResultCount(1, 0)));
+        assert_eq!(env.call::<bool>("one", ()), Err(EvaluationError::UnexpectedElement("boolean", Element::Integer(1))));
+        assert_eq!(env.stack().cloned().collect::<Vec<_>>(), vec![Element::Integer(0)]);
+        assert_matches!(env.call::<i64>("missing", ()), Err(EvaluationError::UndefinedVariable(_)));
+    }
+}
@@ -0,0 +1,173 @@
+use super::{Environment, EvaluationError, EvaluationOperation};
+use crate::element::Element;
+use crate::permissions::Capability;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+const MODULE_EXTENSION: &str = "apn";
+
+type Definitions = HashMap<String, Element>;
+
+#[derive(Debug)]
+pub(super) struct Modules {
+    search_path: Vec<PathBuf>,
+    loaded: HashMap<PathBuf, Vec<(String, Element)>>,
+    loading: Vec<PathBuf>,
+    exports: Option<Vec<String>>,
+}
+
+impl Modules {
+    pub(super) fn new(search_path: Vec<PathBuf>) -> Self {
+        Self {
+            search_path,
+            loaded: HashMap::new(),
+            loading: Vec::new(),
+            exports: None,
+        }
+    }
+}
+
+impl Environment {
+    /// Installs the definitions of module `name` as `name::definition`, evaluating the module
+    /// only the first time it is imported.
+    pub(crate) fn import(&mut self, name: &str) -> Result<(), EvaluationError> {
+        let (path, real) = self.find_module(name)?;
+        let key = real.canonicalize()?;
+        let definitions = match self.modules.loaded.get(&key) {
+            Some(definitions) => definitions.clone(),
+            None => {
+                if self.modules.loading.contains(&key) {
+                    return Err(EvaluationError::CircularImport(name.to_string()));
+                }
+                let source = fs::read_to_string(&real)?;
+                self.modules.loading.push(key.clone());
+                let result = self.evaluate_module(path, source);
+                self.modules.loading.pop();
+                let (definitions, exports) = result?;
+                let namespace = Path::new(name).file_stem().unwrap_or_default().to_string_lossy();
+                let definitions = namespaced(&namespace, definitions, exports);
+                self.modules.loaded.insert(key, definitions.clone());
+                definitions
+            }
+        };
+        for (name, value) in definitions {
+            self.assign(name, value)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn export(&mut self, names: Vec<String>) {
+        self.modules.exports.get_or_insert_default().extend(names);
+    }
+
+    fn find_module(&self, name: &str) -> Result<(PathBuf, PathBuf), EvaluationError> {
+        let file = Path::new(name).with_extension(MODULE_EXTENSION);
+        let mut directories = vec![self.locate("")];
+        directories.extend(self.modules.search_path.iter().cloned());
+        if self.permissions.allows(Capability::EnvironmentVariables)
+            && let Some(paths) = env::var_os("APN_PATH")
+        {
+            directories.extend(env::split_paths(&paths));
+        }
+        for directory in directories {
+            let path = directory.join(&file);
+            let real = self.permissions.path(&path.to_string_lossy())?;
+            if real.is_file() {
+                return Ok((path, real));
+            }
+        }
+        Err(EvaluationError::ModuleNotFound(name.to_string()))
+    }
+
+    /// Evaluates a module with its own variables, returning its definitions and export list.
+    fn evaluate_module(
+        &mut self,
+        path: PathBuf,
+        source: String,
+    ) -> Result<(Definitions, Option<Vec<String>>), EvaluationError> {
+        let variables = std::mem::take(&mut self.variables);
+        let exports = self.modules.exports.take();
+        let mark = self.evaluation_history.len();
+        let result = self.evaluate_file(path, source);
+        let definitions = std::mem::replace(&mut self.variables, variables);
+        let module_exports = std::mem::replace(&mut self.modules.exports, exports);
+        // The module's assignments refer to its own scope, which is gone now.
+        let operations = self.evaluation_history.split_off(mark);
+        for operation in operations {
+            if matches!(operation, EvaluationOperation::Assign(_, _)) {
+                self.memory -= self.recorded(&operation);
+            } else {
+                self.evaluation_history.push(operation);
+            }
+        }
+        self.memory -= definitions.values().map(|e| self.footprint(e)).sum::<usize>();
+        result.map(|_| (definitions, module_exports))
+    }
+}
+
+/// Prefixes every definition with the namespace, hiding those not exported behind a name
+/// scripts cannot spell, and rewrites references between definitions accordingly.
+fn namespaced(
+    namespace: &str,
+    definitions: Definitions,
+    exports: Option<Vec<String>>,
+) -> Vec<(String, Element)> {
+    let renamed = definitions
+        .keys()
+        .map(|name| {
+            let public = exports.as_ref().is_none_or(|exports| exports.contains(name));
+            let separator = if public { "::" } else { "::#" };
+            (name.clone(), format!("{}{}{}", namespace, separator, name))
+        })
+        .collect::<HashMap<_, _>>();
+    let mut definitions = definitions
+        .into_iter()
+        .map(|(name, value)| (renamed[&name].clone(), rename(value, &renamed)))
+        .collect::<Vec<_>>();
+    definitions.sort_by(|a, b| a.0.cmp(&b.0));
+    definitions
+}
+
+fn rename(element: Element, renamed: &HashMap<String, String>) -> Element {
+    match element {
+        Element::Variable(name) => Element::Variable(renamed.get(&name).cloned().unwrap_or(name)),
+        Element::Array(elements) => {
+            Element::Array(elements.into_iter().map(|e| rename(e, renamed)).collect())
+        }
+        Element::Procedure(elements) => {
+            Element::Procedure(elements.into_iter().map(|e| rename(e, renamed)).collect())
+        }
+        Element::Map(entries) => Element::Map(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k, rename(v, renamed)))
+                .collect(),
+        ),
+        element => element,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches::assert_matches;
+
+    #[test]
+    fn evaluates_each_module_once() {
+        let mut env = Environment::builder().search_path("../programs/modules").build();
+        assert_matches!(env.evaluate(":geometry import :math import"), Ok(()));
+        assert_matches!(env.evaluate(":math import"), Ok(()));
+        assert_eq!(env.modules.loaded.len(), 2);
+        assert!(env.modules.loading.is_empty());
+    }
+
+    #[test]
+    fn keeps_definitions_of_rolled_back_import_loadable() {
+        let mut env = Environment::builder().search_path("../programs/modules").build();
+        assert_matches!(env.evaluate(":math import pop"), Err(EvaluationError::EmptyStack));
+        assert_eq!(env.vars().count(), 0);
+        assert_matches!(env.evaluate(":math import 2 $math::fourth ."), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(16)));
+    }
+}
@@ -6,29 +6,49 @@ mod divide;
 mod compare;
 mod assign;
 mod control_if;
+mod control_try;
 mod repeat;
 mod eval;
 mod concatenate;
+mod convert;
+mod dictionary;
 mod rotate;
 mod append;
+mod assert;
 mod make_array;
 mod read;
+mod file;
+mod console;
+mod import;
 mod modulo;
 mod copy;
 mod clear;
 mod map;
 mod range;
+mod shuffle;
+mod combinator;
+mod string;
+mod json;
 
 use crate::{Environment, EvaluationError};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
 
 use crate::function::add::add;
 use crate::function::append::append;
 use crate::function::concatenate::concatenate;
+use crate::function::assert::{assert, assert_eq, assert_stack};
 use crate::function::assign::assign;
 use crate::function::clear::clear;
+use crate::function::combinator::{
+    apply, bi, compose, curry, dip, keep, to_array, to_procedure, tri,
+};
 use crate::function::compare::{equal, greater, greater_equal, less, less_equal};
 use crate::function::control_if::control_if;
+use crate::function::control_try::{control_try, finally, throw};
+use crate::function::convert::{chr, format, ord, parse_float, parse_int, to_string};
 use crate::function::copy::copy;
+use crate::function::dictionary::{entries, get, has_key, keys, put, remove, values};
 use crate::function::divide::divide;
 use crate::function::eval::eval;
 use crate::function::make_array::make_array;
@@ -38,20 +58,85 @@ use crate::function::multiply::multiply;
 use crate::function::pop::pop;
 use crate::function::range::range;
 use crate::function::read::read;
+use crate::function::console::{emit, print, print_stack, println, read_char, read_line};
+use crate::function::file::{append_file, exists, list_dir, read_lines, remove_file, slurp, write_file};
+use crate::function::import::{export, import};
+use crate::function::json::{json_parse, json_stringify};
 use crate::function::repeat::{repeat, repeat_eval};
+use crate::function::shuffle::{depth, drop2, dup2, nip, over, pick, reverse_rot, roll, rot, tuck};
+use crate::function::string::{
+    chars, ends_with, find, join, lines, lower, replace, split, starts_with, trim, upper,
+};
 use crate::function::subtract::subtract;
 use crate::function::rotate::rotate;
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Function(&'static str, fn (&mut Environment) -> Result<(), EvaluationError>);
+/// A word implemented in Rust by the host application.
+pub type HostFunction = dyn Fn(&mut Environment) -> Result<(), EvaluationError> + Send + Sync;
+
+#[derive(Clone)]
+pub enum Function {
+    /// A built-in word: its name, its stack effect and its implementation.
+    Builtin(&'static str, &'static str, fn (&mut Environment) -> Result<(), EvaluationError>),
+    Host(Arc<str>, Arc<HostFunction>),
+}
 
 impl Function {
     pub(super) fn execute(&self, environment: &mut Environment) -> Result<(), EvaluationError> {
-        self.1(environment)
+        match self {
+            Function::Builtin(_, _, f) => f(environment),
+            Function::Host(_, f) => f(environment),
+        }
+    }
+
+    pub(super) fn name(&self) -> &str {
+        match self {
+            Function::Builtin(name, _, _) => name,
+            Function::Host(name, _) => name,
+        }
+    }
+
+    pub(super) fn is_eval(&self) -> bool {
+        matches!(self, Function::Builtin(".", _, _))
+    }
+
+    pub(super) fn signature(&self) -> &'static str {
+        match self {
+            Function::Builtin(_, signature, _) => signature,
+            Function::Host(_, _) => "",
+        }
     }
+}
+
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Function::Builtin(a, _, _), Function::Builtin(b, _, _)) => a == b,
+            (Function::Host(a, f), Function::Host(b, g)) => a == b && Arc::ptr_eq(f, g),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Function {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Resolves built-in words by name; host functions cannot be restored this way.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Function {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Function::try_from(name.as_str())
+            .map_err(|_| serde::de::Error::custom(format!("unknown function {}", name)))
+    }
+}
 
-    pub(super) fn name(&self) -> &'static str {
-        self.0
+impl Debug for Function {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Function").field(&self.name()).finish()
     }
 }
 
@@ -64,35 +149,108 @@ impl TryFrom<&str> for Function {
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             // math
-            "add" | "+" => Ok(Function("+", add)),
-            "subtract" | "-" => Ok(Function("-", subtract)),
-            "multiply" | "*" => Ok(Function("*", multiply)),
-            "divide" | "/" => Ok(Function("/", divide)),
-            "modulo" | "%" => Ok(Function("%", modulo)),
+            "add" | "+" => Ok(Function::Builtin("+", "number number -- number", add)),
+            "subtract" | "-" => Ok(Function::Builtin("-", "number number -- number", subtract)),
+            "multiply" | "*" => Ok(Function::Builtin("*", "number number -- number", multiply)),
+            "divide" | "/" => Ok(Function::Builtin("/", "number number -- float", divide)),
+            "modulo" | "%" => Ok(Function::Builtin("%", "integer integer -- integer", modulo)),
             // comparison
-            "less" | "<" => Ok(Function("<", less)),
-            "less_equal" | "<=" => Ok(Function("<=", less_equal)),
-            "equal" | "==" => Ok(Function("==", equal)),
-            "greater" | ">" => Ok(Function(">", greater)),
-            "greater_equal" | ">=" => Ok(Function(">=", greater_equal)),
+            "less" | "<" => Ok(Function::Builtin("<", "number number -- boolean", less)),
+            "less_equal" | "<=" => Ok(Function::Builtin("<=", "number number -- boolean", less_equal)),
+            "equal" | "==" => Ok(Function::Builtin("==", "a b -- boolean", equal)),
+            "greater" | ">" => Ok(Function::Builtin(">", "number number -- boolean", greater)),
+            "greater_equal" | ">=" => Ok(Function::Builtin(">=", "number number -- boolean", greater_equal)),
             // stack manipulation
-            "pop" => Ok(Function("pop", pop)),
-            "rotate" => Ok(Function("rotate", rotate)),
-            "copy" => Ok(Function("copy", copy)),
-            "repeat" => Ok(Function("repeat", repeat)),
-            "clear" => Ok(Function("clear", clear)),
+            "pop" => Ok(Function::Builtin("pop", "a --", pop)),
+            "rotate" | "swap" => Ok(Function::Builtin("rotate", "a b -- b a", rotate)),
+            "copy" | "dup" => Ok(Function::Builtin("copy", "a -- a a", copy)),
+            "repeat" => Ok(Function::Builtin("repeat", "a integer -- a...", repeat)),
+            "clear" => Ok(Function::Builtin("clear", "... --", clear)),
+            "over" => Ok(Function::Builtin("over", "a b -- a b a", over)),
+            "rot" => Ok(Function::Builtin("rot", "a b c -- b c a", rot)),
+            "-rot" => Ok(Function::Builtin("-rot", "a b c -- c a b", reverse_rot)),
+            "nip" => Ok(Function::Builtin("nip", "a b -- b", nip)),
+            "tuck" => Ok(Function::Builtin("tuck", "a b -- b a b", tuck)),
+            "pick" => Ok(Function::Builtin("pick", "... integer -- ...", pick)),
+            "roll" => Ok(Function::Builtin("roll", "... integer -- ...", roll)),
+            "dup2" => Ok(Function::Builtin("dup2", "a b -- a b a b", dup2)),
+            "drop2" => Ok(Function::Builtin("drop2", "a b --", drop2)),
+            "depth" => Ok(Function::Builtin("depth", "-- integer", depth)),
+            // conversion
+            "to_string" => Ok(Function::Builtin("to_string", "a -- string", to_string)),
+            "parse_int" => Ok(Function::Builtin("parse_int", "string -- integer", parse_int)),
+            "parse_float" => Ok(Function::Builtin("parse_float", "string -- float", parse_float)),
+            "ord" => Ok(Function::Builtin("ord", "char -- integer", ord)),
+            "chr" => Ok(Function::Builtin("chr", "integer -- char", chr)),
+            "format" => Ok(Function::Builtin("format", "... string -- string", format)),
             // control flow
-            "assign" | "=" => Ok(Function("=", assign)),
-            "if" => Ok(Function("if", control_if)),
-            "eval" | "." => Ok(Function(".", eval)),
-            "repeat_eval" | "repeat." => Ok(Function("repeat.", repeat_eval)),
-            "read" => Ok(Function("read", read)),
+            "assign" | "=" => Ok(Function::Builtin("=", "a variable --", assign)),
+            "if" => Ok(Function::Builtin("if", "boolean a b -- a|b", control_if)),
+            "eval" | "." => Ok(Function::Builtin(".", "procedure -- ...", eval)),
+            "repeat_eval" | "repeat." => Ok(Function::Builtin("repeat.", "procedure integer -- ...", repeat_eval)),
+            "read" => Ok(Function::Builtin("read", "string -- ...", read)),
+            "import" => Ok(Function::Builtin("import", "symbol|string --", import)),
+            "export" => Ok(Function::Builtin("export", "symbol|array --", export)),
+            "try" => Ok(Function::Builtin("try", "procedure procedure -- ...", control_try)),
+            "throw" => Ok(Function::Builtin("throw", "a --", throw)),
+            "finally" => Ok(Function::Builtin("finally", "procedure procedure -- ...", finally)),
+            "dip" => Ok(Function::Builtin("dip", "a procedure -- ... a", dip)),
+            "keep" => Ok(Function::Builtin("keep", "a procedure -- ... a", keep)),
+            "bi" => Ok(Function::Builtin("bi", "a procedure procedure -- ...", bi)),
+            "tri" => Ok(Function::Builtin("tri", "a procedure procedure procedure -- ...", tri)),
+            "compose" => Ok(Function::Builtin("compose", "procedure procedure -- procedure", compose)),
+            "curry" => Ok(Function::Builtin("curry", "a procedure -- procedure", curry)),
+            "apply" => Ok(Function::Builtin("apply", "array procedure -- ...", apply)),
+            "to_procedure" => Ok(Function::Builtin("to_procedure", "array -- procedure", to_procedure)),
+            "to_array" => Ok(Function::Builtin("to_array", "procedure -- array", to_array)),
             // array manipulation
-            "concatenate" | "concat" | "++" => Ok(Function("++", concatenate)),
-            "append" => Ok(Function("append", append)),
-            "make_array" => Ok(Function("make_array", make_array)),
-            "map" => Ok(Function("map", map)),
-            "range" => Ok(Function("range", range)),
+            "concatenate" | "concat" | "++" => Ok(Function::Builtin("++", "array array -- array", concatenate)),
+            "append" => Ok(Function::Builtin("append", "array a -- array", append)),
+            "make_array" => Ok(Function::Builtin("make_array", "... integer -- array", make_array)),
+            "map" => Ok(Function::Builtin("map", "array|map procedure -- array|map", map)),
+            "range" => Ok(Function::Builtin("range", "integer integer -- array", range)),
+            // map manipulation
+            "get" => Ok(Function::Builtin("get", "map key -- a", get)),
+            "put" => Ok(Function::Builtin("put", "map key a -- map", put)),
+            "remove" => Ok(Function::Builtin("remove", "map key -- map", remove)),
+            "keys" => Ok(Function::Builtin("keys", "map -- array", keys)),
+            "values" => Ok(Function::Builtin("values", "map -- array", values)),
+            "has_key" => Ok(Function::Builtin("has_key", "map key -- boolean", has_key)),
+            "entries" => Ok(Function::Builtin("entries", "map -- array", entries)),
+            // string manipulation
+            "split" => Ok(Function::Builtin("split", "string string -- array", split)),
+            "join" => Ok(Function::Builtin("join", "array string -- string", join)),
+            "trim" => Ok(Function::Builtin("trim", "string -- string", trim)),
+            "upper" => Ok(Function::Builtin("upper", "string -- string", upper)),
+            "lower" => Ok(Function::Builtin("lower", "string -- string", lower)),
+            "starts_with" => Ok(Function::Builtin("starts_with", "string string -- boolean", starts_with)),
+            "ends_with" => Ok(Function::Builtin("ends_with", "string string -- boolean", ends_with)),
+            "find" => Ok(Function::Builtin("find", "string string -- integer", find)),
+            "replace" => Ok(Function::Builtin("replace", "string string string -- string", replace)),
+            "chars" => Ok(Function::Builtin("chars", "string -- array", chars)),
+            "lines" => Ok(Function::Builtin("lines", "string -- array", lines)),
+            // files
+            "slurp" => Ok(Function::Builtin("slurp", "string -- string", slurp)),
+            "write_file" | "spit" => Ok(Function::Builtin("write_file", "string string --", write_file)),
+            "append_file" => Ok(Function::Builtin("append_file", "string string --", append_file)),
+            "read_lines" => Ok(Function::Builtin("read_lines", "string -- array", read_lines)),
+            "exists" => Ok(Function::Builtin("exists", "string -- boolean", exists)),
+            "list_dir" => Ok(Function::Builtin("list_dir", "string -- array", list_dir)),
+            "remove_file" => Ok(Function::Builtin("remove_file", "string --", remove_file)),
+            // console
+            "print" => Ok(Function::Builtin("print", "a --", print)),
+            "println" => Ok(Function::Builtin("println", "a --", println)),
+            "emit" => Ok(Function::Builtin("emit", "char|integer --", emit)),
+            ".s" => Ok(Function::Builtin(".s", "--", print_stack)),
+            "read_line" => Ok(Function::Builtin("read_line", "-- string", read_line)),
+            "read_char" => Ok(Function::Builtin("read_char", "-- char", read_char)),
+            // json
+            "json_parse" => Ok(Function::Builtin("json_parse", "string -- a", json_parse)),
+            "json_stringify" => Ok(Function::Builtin("json_stringify", "a -- string", json_stringify)),
+            // testing
+            "assert" => Ok(Function::Builtin("assert", "boolean --", assert)),
+            "assert_eq" => Ok(Function::Builtin("assert_eq", "a b --", assert_eq)),
+            "assert_stack" => Ok(Function::Builtin("assert_stack", "array --", assert_stack)),
             // error
             _ => Err(()),
         }
@@ -2,15 +2,20 @@ use crate::{Element, Environment, EvaluationError};
 
 pub(super) fn append(environment: &mut Environment) -> Result<(), EvaluationError> {
     if let Ok(b) = environment.pop() {
-        if let Ok(Element::Array(a)) = environment.pop() {
-            let mut c = a.clone();
-            c.push(b);
-            environment.push(Element::Array(c))
-        } else {
-            Err(EvaluationError::InvalidStackElements)
+        match (environment.pop(), b) {
+            (Ok(Element::String(mut a)), Element::Char(b)) => {
+                a.push(b);
+                environment.push(Element::String(a))
+            }
+            (Ok(a @ (Element::Array(_) | Element::String(_))), b) => {
+                let mut c = a.into_array()?;
+                c.push(b);
+                environment.push(Element::Array(c))
+            }
+            _ => Err(EvaluationError::InvalidStackElements(None)),
         }
     } else {
-        Err(EvaluationError::InvalidStackElements)
+        Err(EvaluationError::InvalidStackElements(None))
     }
 }
 
@@ -33,4 +38,12 @@ mod tests {
         }
         assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
     }
+
+    #[test]
+    fn appends_char_after_string() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("\"ab\" 'c' append \"ab\" 1 append"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Array(array)) if array == vec![Element::Char('a'), Element::Char('b'), Element::Integer(1)]);
+        assert_matches!(env.pop(), Ok(Element::String(string)) if string == "abc");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,70 @@
+use crate::{Element, Environment, EvaluationError};
+
+pub(super) fn assert(environment: &mut Environment) -> Result<(), EvaluationError> {
+    match environment.pop_value()? {
+        Element::Boolean(true) => Ok(()),
+        Element::Boolean(false) => Err(EvaluationError::AssertionFailed(
+            vec![Element::Boolean(true)],
+            vec![Element::Boolean(false)],
+        )),
+        _ => Err(EvaluationError::InvalidStackElements(None)),
+    }
+}
+
+pub(super) fn assert_eq(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let expected = environment.pop_value()?;
+    let actual = environment.pop_value()?;
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(EvaluationError::AssertionFailed(vec![expected], vec![actual]))
+    }
+}
+
+pub(super) fn assert_stack(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let expected = environment.pop_value()?.into_array()?;
+    let actual = environment
+        .stack()
+        .map(|e| environment.resolve(e))
+        .collect::<Result<Vec<Element>, EvaluationError>>()?;
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(EvaluationError::AssertionFailed(expected, actual))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches::assert_matches;
+
+    #[test]
+    fn asserts_boolean_is_true() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("1 1 == assert"), Ok(()));
+        assert_matches!(env.evaluate("1 2 == assert"), Err(EvaluationError::AssertionFailed(_, _)));
+        assert_matches!(env.evaluate("1 assert"), Err(EvaluationError::InvalidStackElements(_)));
+    }
+
+    #[test]
+    fn asserts_two_elements_are_equal() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("[1 \"a\"] [1 \"a\"] assert_eq"), Ok(()));
+        let error = env.evaluate("1 2 + 4 assert_eq").unwrap_err();
+        assert_eq!(error, EvaluationError::AssertionFailed(vec![Element::Integer(4)], vec![Element::Integer(3)]));
+        assert_eq!(error.to_string(), "assertion failed\n  expected: [ 4 ]\n  actual:   [ 3 ]\n  at 0: expected 4, found 3");
+    }
+
+    #[test]
+    fn asserts_whole_stack_without_consuming_it() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("1 $x = $x 2 [1 2] assert_stack"), Ok(()));
+        assert_eq!(env.stack_len(), 2);
+        let error = env.evaluate("[1 3 4] assert_stack").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "assertion failed\n  expected: [ 1 3 4 ]\n  actual:   [ 1 2 ]\n  at 1: expected 3, found 2\n  at 2: expected 4, found nothing"
+        );
+    }
+}
@@ -7,7 +7,7 @@ pub(super) fn assign(environment: &mut Environment) -> Result<(), EvaluationErro
         let value = environment.pop()?;
         environment.assign(name, value)
     } else {
-        Err(EvaluationError::InvalidStackElements)
+        Err(EvaluationError::InvalidStackElements(None))
     }
 }
 
@@ -0,0 +1,163 @@
+use crate::function::eval::call;
+use crate::{Element, Environment, EvaluationError};
+
+fn pop_procedure(environment: &mut Environment) -> Result<Vec<Element>, EvaluationError> {
+    if let Element::Procedure(elements) = environment.pop_value()? {
+        Ok(elements)
+    } else {
+        Err(EvaluationError::InvalidStackElements(None))
+    }
+}
+
+pub(super) fn dip(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let procedure = environment.pop()?;
+    let element = environment.pop()?;
+    call(environment, procedure)?;
+    environment.push(element)
+}
+
+pub(super) fn keep(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let procedure = environment.pop()?;
+    let element = environment.pop()?;
+    environment.push(element.clone())?;
+    call(environment, procedure)?;
+    environment.push(element)
+}
+
+pub(super) fn bi(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let second = environment.pop()?;
+    let first = environment.pop()?;
+    let element = environment.pop()?;
+    for procedure in [first, second] {
+        environment.push(element.clone())?;
+        call(environment, procedure)?;
+    }
+    Ok(())
+}
+
+pub(super) fn tri(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let third = environment.pop()?;
+    let second = environment.pop()?;
+    let first = environment.pop()?;
+    let element = environment.pop()?;
+    for procedure in [first, second, third] {
+        environment.push(element.clone())?;
+        call(environment, procedure)?;
+    }
+    Ok(())
+}
+
+pub(super) fn compose(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let second = pop_procedure(environment)?;
+    let mut first = pop_procedure(environment)?;
+    first.extend(second);
+    environment.push(Element::Procedure(first))
+}
+
+pub(super) fn curry(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let mut procedure = pop_procedure(environment)?;
+    let value = environment.pop_value()?;
+    procedure.insert(0, value);
+    environment.push(Element::Procedure(procedure))
+}
+
+pub(super) fn apply(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let procedure = environment.pop()?;
+    let arguments = environment.pop_value()?.into_array()?;
+    for argument in arguments {
+        environment.push(argument)?;
+    }
+    call(environment, procedure)
+}
+
+pub(super) fn to_procedure(environment: &mut Environment) -> Result<(), EvaluationError> {
+    match environment.pop_value()? {
+        procedure @ Element::Procedure(_) => environment.push(procedure),
+        element => environment.push(Element::Procedure(element.into_array()?)),
+    }
+}
+
+pub(super) fn to_array(environment: &mut Environment) -> Result<(), EvaluationError> {
+    match environment.pop_value()? {
+        Element::Procedure(elements) => environment.push(Element::Array(elements)),
+        element => environment.push(Element::Array(element.into_array()?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches::assert_matches;
+
+    #[test]
+    fn runs_procedure_under_top_element() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("1 2 10 {+} dip"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(10)));
+        assert_matches!(env.pop(), Ok(Element::Integer(3)));
+        assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
+    }
+
+    #[test]
+    fn keeps_element_after_procedure() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("2 {3 *} keep"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(2)));
+        assert_matches!(env.pop(), Ok(Element::Integer(6)));
+        assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
+    }
+
+    #[test]
+    fn applies_multiple_procedures_to_one_element() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("4 {1 +} {2 *} {copy *} tri 5 {1 -} {} bi"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(5)));
+        assert_matches!(env.pop(), Ok(Element::Integer(4)));
+        assert_matches!(env.pop(), Ok(Element::Integer(16)));
+        assert_matches!(env.pop(), Ok(Element::Integer(8)));
+        assert_matches!(env.pop(), Ok(Element::Integer(5)));
+        assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
+    }
+
+    #[test]
+    fn composes_two_procedures() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("{1 +} {2 *} compose $f = 3 $f ."), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(8)));
+        assert_matches!(env.evaluate("{1 +} [2 *] compose"), Err(EvaluationError::InvalidStackElements(_)));
+    }
+
+    #[test]
+    fn curries_value_into_procedure() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("10 {-} curry"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Procedure(elements)) if elements.len() == 2 && elements[0] == Element::Integer(10));
+        assert_matches!(env.evaluate("1 5 {-} curry ."), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(-4)));
+    }
+
+    #[test]
+    fn applies_procedure_to_argument_array() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("[7 3] {-} apply"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(4)));
+        assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
+    }
+
+    #[test]
+    fn converts_between_arrays_and_procedures() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("[1 2 +] to_procedure . {3 4} to_array"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Array(array)) if array == vec![Element::Integer(3), Element::Integer(4)]);
+        assert_matches!(env.pop(), Ok(Element::Integer(3)));
+    }
+
+    #[test]
+    fn rolls_back_when_procedure_fails() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("1 2"), Ok(()));
+        let error = env.evaluate("{+ +} dip").unwrap_err();
+        assert_matches!(error.root(), EvaluationError::EmptyStack);
+        assert_eq!(env.stack().cloned().collect::<Vec<_>>(), vec![Element::Integer(1), Element::Integer(2)]);
+    }
+}
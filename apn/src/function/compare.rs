@@ -1,4 +1,4 @@
-use crate::element::Element::{Boolean, Float, Integer};
+use crate::element::Element::{self, Boolean, Float, Integer};
 use crate::{Environment, EvaluationError};
 
 pub(super) fn less(environment: &mut Environment) -> Result<(), EvaluationError> {
@@ -9,7 +9,7 @@ pub(super) fn less(environment: &mut Environment) -> Result<(), EvaluationError>
         (Integer(a), Float(b)) => Boolean((a as f64) < b),
         (Float(a), Integer(b)) => Boolean(a < b as f64),
         (Float(a), Float(b)) => Boolean(a < b),
-        _ => return Err(EvaluationError::FunctionNotApplicable),
+        _ => return Err(EvaluationError::FunctionNotApplicable(None)),
     })
 }
 
@@ -21,7 +21,7 @@ pub(super) fn less_equal(environment: &mut Environment) -> Result<(), Evaluation
         (Integer(a), Float(b)) => Boolean((a as f64) <= b),
         (Float(a), Integer(b)) => Boolean(a <= b as f64),
         (Float(a), Float(b)) => Boolean(a <= b),
-        _ => return Err(EvaluationError::FunctionNotApplicable),
+        _ => return Err(EvaluationError::FunctionNotApplicable(None)),
     })
 }
 
@@ -34,7 +34,12 @@ pub(super) fn equal(environment: &mut Environment) -> Result<(), EvaluationError
         (Float(a), Integer(b)) => Boolean(a == b as f64),
         (Float(a), Float(b)) => Boolean(a == b),
         (Boolean(a), Boolean(b)) => Boolean(a == b),
-        _ => return Err(EvaluationError::FunctionNotApplicable),
+        (Element::String(a), Element::String(b)) => Boolean(a == b),
+        (Element::String(a), b @ Element::Array(_)) | (b @ Element::Array(_), Element::String(a)) => {
+            Boolean(b.as_string().is_ok_and(|b| b == a))
+        }
+        (Element::Symbol(a), Element::Symbol(b)) => Boolean(a == b),
+        _ => return Err(EvaluationError::FunctionNotApplicable(None)),
     })
 }
 
@@ -46,7 +51,7 @@ pub(super) fn greater(environment: &mut Environment) -> Result<(), EvaluationErr
         (Integer(a), Float(b)) => Boolean((a as f64) > b),
         (Float(a), Integer(b)) => Boolean(a > b as f64),
         (Float(a), Float(b)) => Boolean(a > b),
-        _ => return Err(EvaluationError::FunctionNotApplicable),
+        _ => return Err(EvaluationError::FunctionNotApplicable(None)),
     })
 }
 
@@ -58,7 +63,7 @@ pub(super) fn greater_equal(environment: &mut Environment) -> Result<(), Evaluat
         (Integer(a), Float(b)) => Boolean((a as f64) >= b),
         (Float(a), Integer(b)) => Boolean(a >= b as f64),
         (Float(a), Float(b)) => Boolean(a >= b),
-        _ => return Err(EvaluationError::FunctionNotApplicable),
+        _ => return Err(EvaluationError::FunctionNotApplicable(None)),
     })
 }
 
@@ -79,4 +84,29 @@ mod tests {
             .collect();
         assert_eq!(results, vec![true, false, true, false, true]);
     }
+
+    #[test]
+    fn compares_strings_for_equality() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("\"ab\" \"ab\" == \"ab\" \"ba\" =="), Ok(()));
+        assert_matches!(env.pop(), Ok(Boolean(false)));
+        assert_matches!(env.pop(), Ok(Boolean(true)));
+    }
+
+    #[test]
+    fn compares_strings_with_char_arrays() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("\"ab\" chars \"ab\" == \"ab\" ['a' 'c'] == [1 2] \"ab\" =="), Ok(()));
+        assert_matches!(env.pop(), Ok(Boolean(false)));
+        assert_matches!(env.pop(), Ok(Boolean(false)));
+        assert_matches!(env.pop(), Ok(Boolean(true)));
+    }
+
+    #[test]
+    fn compares_symbols_by_name() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate(":red :red == :red :blue =="), Ok(()));
+        assert_matches!(env.pop(), Ok(Boolean(false)));
+        assert_matches!(env.pop(), Ok(Boolean(true)));
+    }
 }
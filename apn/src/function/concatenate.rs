@@ -2,17 +2,22 @@ use crate::{Environment, EvaluationError};
 use crate::element::Element;
 
 pub(super) fn concatenate(environment: &mut Environment) -> Result<(), EvaluationError> {
-    if let Ok(Element::Array(b)) = environment.pop() {
-        if let Ok(Element::Array(a)) = environment.pop() {
-            let c = a.into_iter()
-                .chain(b.into_iter())
+    let b = environment.pop()?;
+    let a = environment.pop()?;
+    environment.reserve(a.size().saturating_add(b.size()))?;
+    match (a, b) {
+        (Element::String(a), Element::String(b)) => environment.push(Element::String(a + &b)),
+        (Element::Procedure(mut a), Element::Procedure(b)) => {
+            a.extend(b);
+            environment.push(Element::Procedure(a))
+        }
+        (a, b) => {
+            let c = a.into_array()?
+                .into_iter()
+                .chain(b.into_array()?)
                 .collect();
             environment.push(Element::Array(c))
-        } else {
-            Err(EvaluationError::InvalidStackElements)
         }
-    } else {
-        Err(EvaluationError::InvalidStackElements)
     }
 }
 
@@ -34,4 +39,19 @@ mod tests {
         }
         assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
     }
+
+    #[test]
+    fn concatenates_strings_and_arrays() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("\"ab\" \"cd\" ++ \"a\" [1] ++"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Array(array)) if array == vec![Element::Char('a'), Element::Integer(1)]);
+        assert_matches!(env.pop(), Ok(Element::String(string)) if string == "abcd");
+    }
+
+    #[test]
+    fn concatenates_two_procedures() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("{1} {2} ++"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Procedure(elements)) if elements == vec![Element::Integer(1), Element::Integer(2)]);
+    }
 }
\ No newline at end of file
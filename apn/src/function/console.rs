@@ -0,0 +1,130 @@
+use crate::{Element, Environment, EvaluationError};
+
+fn write(environment: &mut Environment, text: &str) -> Result<(), EvaluationError> {
+    let output = environment.output()?;
+    output.write_all(text.as_bytes())?;
+    output.flush()?;
+    Ok(())
+}
+
+pub(super) fn print(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let text = environment.pop_value()?.to_text();
+    write(environment, &text)
+}
+
+pub(super) fn println(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let text = environment.pop_value()?.to_text();
+    write(environment, &format!("{}\n", text))
+}
+
+pub(super) fn emit(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let c = match environment.pop_value()? {
+        Element::Char(c) => c,
+        Element::Integer(code) => u32::try_from(code)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or(EvaluationError::NotACharacter)?,
+        _ => return Err(EvaluationError::InvalidStackElements(None)),
+    };
+    write(environment, &c.to_string())
+}
+
+pub(super) fn print_stack(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let mut text = format!("<{}>", environment.stack_len());
+    for element in environment.stack() {
+        text.push(' ');
+        text.push_str(&element.to_string());
+    }
+    text.push('\n');
+    write(environment, &text)
+}
+
+pub(super) fn read_line(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let mut line = String::new();
+    environment.input()?.read_line(&mut line)?;
+    environment.push(Element::String(line))
+}
+
+pub(super) fn read_char(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let input = environment.input()?;
+    let mut bytes = [0; 4];
+    if input.read(&mut bytes[..1])? == 0 {
+        return Err(EvaluationError::IO("end of input".to_string()));
+    }
+    let length = match bytes[0] {
+        0xf0.. => 4,
+        0xe0.. => 3,
+        0xc0.. => 2,
+        _ => 1,
+    };
+    input.read_exact(&mut bytes[1..length])?;
+    let c = std::str::from_utf8(&bytes[..length])
+        .ok()
+        .and_then(|s| s.chars().next())
+        .ok_or(EvaluationError::NotACharacter)?;
+    environment.push(Element::Char(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Capability;
+    use std::assert_matches::assert_matches;
+    use std::io::{self, Cursor, Write};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Output {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn prints_elements_to_output() {
+        let output = Output::default();
+        let mut env = Environment::builder().output(output.clone()).build();
+        assert_matches!(env.evaluate("\"a\" print 'b' print 1 2 + println :c println 'd' emit 101 emit"), Ok(()));
+        assert_eq!(output.text(), "ab3\n:c\nde");
+        assert_eq!(env.stack_len(), 0);
+    }
+
+    #[test]
+    fn prints_stack_without_consuming_it() {
+        let output = Output::default();
+        let mut env = Environment::builder().output(output.clone()).build();
+        assert_matches!(env.evaluate("1 \"two\" [3] .s"), Ok(()));
+        assert_eq!(output.text(), "<3> 1 \"two\" [ 3 ]\n");
+        assert_eq!(env.stack_len(), 3);
+    }
+
+    #[test]
+    fn reads_lines_and_chars_from_input() {
+        let mut env = Environment::builder().input(Cursor::new("hi\nébc")).build();
+        assert_matches!(env.evaluate("read_line read_char read_line read_line"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::String(s)) if s.is_empty());
+        assert_matches!(env.pop(), Ok(Element::String(s)) if s == "bc");
+        assert_matches!(env.pop(), Ok(Element::Char('é')));
+        assert_matches!(env.pop(), Ok(Element::String(s)) if s == "hi\n");
+        assert_matches!(env.evaluate("read_char"), Err(EvaluationError::IO(_)));
+    }
+
+    #[test]
+    fn requires_console_capabilities() {
+        let mut env = Environment::builder().deny(Capability::Stdout).deny(Capability::Stdin).build();
+        assert_matches!(env.evaluate("1 println"), Err(EvaluationError::PermissionDenied(_)));
+        assert_matches!(env.evaluate("read_line"), Err(EvaluationError::PermissionDenied(_)));
+    }
+}
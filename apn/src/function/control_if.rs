@@ -7,7 +7,7 @@ pub(super) fn control_if(environment: &mut Environment) -> Result<(), Evaluation
     match environment.pop_value()? {
         Element::Boolean(true) => environment.push(result_true),
         Element::Boolean(false) => environment.push(result_false),
-        _ => Err(EvaluationError::InvalidStackElements),
+        _ => Err(EvaluationError::InvalidStackElements(None)),
     }
 }
 
@@ -0,0 +1,89 @@
+use crate::element::Key;
+use crate::function::eval::call;
+use crate::{Element, Environment, EvaluationError};
+use std::collections::BTreeMap;
+
+fn error_value(error: &EvaluationError) -> Element {
+    let mut map = BTreeMap::from([
+        (Key::Symbol("kind".to_string()), Element::Symbol(error.kind().to_string())),
+        (Key::Symbol("message".to_string()), Element::String(error.to_string())),
+    ]);
+    if let EvaluationError::Thrown(value) = error.root() {
+        map.insert(Key::Symbol("value".to_string()), value.clone());
+    }
+    Element::Map(map)
+}
+
+pub(super) fn control_try(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let handler = environment.pop()?;
+    let body = environment.pop()?;
+    if let Err(error) = environment.attempt(|environment| call(environment, body)) {
+        environment.push(error_value(&error))?;
+        call(environment, handler)?;
+    }
+    Ok(())
+}
+
+pub(super) fn throw(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let value = environment.pop_value()?;
+    Err(EvaluationError::Thrown(value))
+}
+
+pub(super) fn finally(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let cleanup = environment.pop()?;
+    let body = environment.pop()?;
+    let result = environment.attempt(|environment| call(environment, body));
+    call(environment, cleanup)?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches::assert_matches;
+
+    #[test]
+    fn runs_body_when_nothing_fails() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("{ 1 2 + } { pop 0 } try"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(3)));
+        assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
+    }
+
+    #[test]
+    fn rolls_back_body_and_runs_handler_with_error() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("5 { 7 $x = 1 0 / } { :kind get } try"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Symbol(kind)) if kind == "division_by_zero");
+        assert_matches!(env.pop(), Ok(Element::Integer(5)));
+        assert_matches!(env.evaluate("$x"), Ok(()));
+        assert_matches!(env.pop_value(), Err(EvaluationError::UndefinedVariable(_)));
+    }
+
+    #[test]
+    fn recovers_from_missing_file() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("{ \"missing.apn\" read } { :message get } try"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::String(message)) if message.starts_with("I/O error"));
+    }
+
+    #[test]
+    fn catches_thrown_values() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("{ :oops throw } { :value get } try"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Symbol(value)) if value == "oops");
+        assert_matches!(env.evaluate("\"bad\" throw"), Err(EvaluationError::Thrown(Element::String(_))));
+    }
+
+    #[test]
+    fn runs_cleanup_and_propagates_error() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("{ 1 } { 2 } finally"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(2)));
+        assert_matches!(env.pop(), Ok(Element::Integer(1)));
+        assert_matches!(env.evaluate("{ { 1 0 / } { 2 } finally } { pop } try"), Ok(()));
+        assert_eq!(env.stack_len(), 0);
+        let error = env.evaluate("{ 1 0 / } { 2 } finally").unwrap_err();
+        assert_matches!(error.root(), EvaluationError::DivisionByZero);
+    }
+}
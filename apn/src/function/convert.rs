@@ -0,0 +1,112 @@
+use crate::{Element, Environment, EvaluationError};
+
+pub(super) fn to_string(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let element = environment.pop_value()?;
+    environment.push(Element::new_string(&element.to_text()))
+}
+
+pub(super) fn parse_int(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let string = environment.pop_string()?;
+    match string.trim().parse::<i64>() {
+        Ok(integer) => environment.push(Element::Integer(integer)),
+        Err(_) => Err(EvaluationError::NotANumber(string)),
+    }
+}
+
+pub(super) fn parse_float(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let string = environment.pop_string()?;
+    match string.trim().parse::<f64>() {
+        Ok(float) => environment.push(Element::Float(float)),
+        Err(_) => Err(EvaluationError::NotANumber(string)),
+    }
+}
+
+pub(super) fn ord(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let c = environment.pop_value()?.as_char()?;
+    environment.push(Element::Integer(c as i64))
+}
+
+pub(super) fn chr(environment: &mut Environment) -> Result<(), EvaluationError> {
+    if let Element::Integer(code) = environment.pop_value()? {
+        match u32::try_from(code).ok().and_then(char::from_u32) {
+            Some(c) => environment.push(Element::Char(c)),
+            None => Err(EvaluationError::NotACharacter),
+        }
+    } else {
+        Err(EvaluationError::InvalidStackElements(None))
+    }
+}
+
+pub(super) fn format(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let template = environment.pop_string()?;
+    let mut pieces = vec![String::new()];
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                pieces.last_mut().unwrap().push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                pieces.push(String::new());
+            }
+            _ => pieces.last_mut().unwrap().push(c),
+        }
+    }
+    let mut values = Vec::new();
+    for _ in 1..pieces.len() {
+        values.push(environment.pop_value()?);
+    }
+    environment.reserve(values.iter().map(Element::size).fold(template.len(), usize::saturating_add))?;
+    let mut result = pieces[0].clone();
+    for piece in &pieces[1..] {
+        result.push_str(&values.pop().unwrap().to_text());
+        result.push_str(piece);
+    }
+    environment.push(Element::new_string(&result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches::assert_matches;
+
+    #[test]
+    fn converts_elements_to_strings() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("42 to_string 1.5 to_string \"abc\" to_string 'x' to_string [1 \"a\"] to_string"), Ok(()));
+        assert_eq!(env.pop_string(), Ok("[ 1 \"a\" ]".to_string()));
+        assert_eq!(env.pop_string(), Ok("x".to_string()));
+        assert_eq!(env.pop_string(), Ok("abc".to_string()));
+        assert_eq!(env.pop_string(), Ok("1.5".to_string()));
+        assert_eq!(env.pop_string(), Ok("42".to_string()));
+    }
+
+    #[test]
+    fn parses_numbers_from_strings() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("\"42\" parse_int \" 2.5\" parse_float"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Float(2.5)));
+        assert_matches!(env.pop(), Ok(Element::Integer(42)));
+        assert_matches!(env.evaluate("\"4x\" parse_int"), Err(EvaluationError::NotANumber(s)) if s == "4x");
+    }
+
+    #[test]
+    fn converts_between_chars_and_codepoints() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("'a' ord 98 chr"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Char('b')));
+        assert_matches!(env.pop(), Ok(Element::Integer(97)));
+        assert_matches!(env.evaluate("-1 chr"), Err(EvaluationError::NotACharacter));
+        assert_matches!(env.evaluate("1 ord"), Err(EvaluationError::NotACharacter));
+    }
+
+    #[test]
+    fn formats_values_from_stack() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("1 \"two\" 'c' \"{} {} {} {{}}\" format"), Ok(()));
+        assert_eq!(env.pop_string(), Ok("1 two c {}".to_string()));
+        assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
+    }
+}
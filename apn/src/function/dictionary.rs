@@ -0,0 +1,120 @@
+use crate::element::Key;
+use crate::{Element, Environment, EvaluationError};
+use std::collections::BTreeMap;
+
+fn pop_map(environment: &mut Environment) -> Result<BTreeMap<Key, Element>, EvaluationError> {
+    if let Element::Map(map) = environment.pop_value()? {
+        Ok(map)
+    } else {
+        Err(EvaluationError::InvalidStackElements(None))
+    }
+}
+
+fn pop_key(environment: &mut Environment) -> Result<Key, EvaluationError> {
+    Key::try_from(environment.pop_value()?)
+}
+
+pub(super) fn get(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let key = pop_key(environment)?;
+    let mut map = pop_map(environment)?;
+    match map.remove(&key) {
+        Some(value) => environment.push(value),
+        None => Err(EvaluationError::KeyNotFound(key.to_string())),
+    }
+}
+
+pub(super) fn put(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let value = environment.pop()?;
+    let key = pop_key(environment)?;
+    let mut map = pop_map(environment)?;
+    map.insert(key, value);
+    environment.push(Element::Map(map))
+}
+
+pub(super) fn remove(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let key = pop_key(environment)?;
+    let mut map = pop_map(environment)?;
+    map.remove(&key);
+    environment.push(Element::Map(map))
+}
+
+pub(super) fn keys(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let map = pop_map(environment)?;
+    environment.push(Element::Array(map.into_keys().map(Element::from).collect()))
+}
+
+pub(super) fn values(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let map = pop_map(environment)?;
+    environment.push(Element::Array(map.into_values().collect()))
+}
+
+pub(super) fn has_key(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let key = pop_key(environment)?;
+    let map = pop_map(environment)?;
+    environment.push(Element::Boolean(map.contains_key(&key)))
+}
+
+pub(super) fn entries(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let map = pop_map(environment)?;
+    environment.push(Element::Array(
+        map.into_iter()
+            .map(|(k, v)| Element::Array(vec![Element::from(k), v]))
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches::assert_matches;
+
+    #[test]
+    fn gets_value_by_key() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("#{ \"a\" 1 'b' 2 } 'b' get"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(2)));
+        assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
+    }
+
+    #[test]
+    fn errs_on_missing_key() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("#{ \"a\" 1 } \"c\" get"), Err(EvaluationError::KeyNotFound(k)) if k == "\"c\"");
+        assert_eq!(env.stack_len(), 0);
+    }
+
+    #[test]
+    fn uses_symbols_as_keys() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("#{ :red 1 :green 2 } :green get"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(2)));
+    }
+
+    #[test]
+    fn puts_and_removes_entries() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("#{ 1 true } 2 false put 1 remove"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Map(map)) if map == BTreeMap::from([(Key::Integer(2), Element::Boolean(false))]));
+    }
+
+    #[test]
+    fn checks_for_keys() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("#{ true 1 } true has_key #{ true 1 } false has_key"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Boolean(false)));
+        assert_matches!(env.pop(), Ok(Element::Boolean(true)));
+        assert_matches!(env.evaluate("#{ } 1.5 has_key"), Err(EvaluationError::NotAKey));
+    }
+
+    #[test]
+    fn lists_keys_values_and_entries() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("#{ \"b\" 2 \"a\" 1 } $m = $m keys $m values $m entries"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Array(array)) if array == vec![
+            Element::Array(vec![Element::new_string("a"), Element::Integer(1)]),
+            Element::Array(vec![Element::new_string("b"), Element::Integer(2)]),
+        ]);
+        assert_matches!(env.pop(), Ok(Element::Array(array)) if array == vec![Element::Integer(1), Element::Integer(2)]);
+        assert_matches!(env.pop(), Ok(Element::Array(array)) if array == vec![Element::new_string("a"), Element::new_string("b")]);
+    }
+}
@@ -2,17 +2,52 @@ use crate::element::Element;
 use crate::{Environment, EvaluationError};
 
 pub(super) fn eval(environment: &mut Environment) -> Result<(), EvaluationError> {
-    let element = environment.pop_value()?;
-    if let Element::Procedure(elements) = element {
-        for e in elements {
-            environment.push(e)?;
-        }
-        Ok(())
+    let element = environment.pop()?;
+    let mut frame = frame_name(&element);
+    let element = environment.resolve(&element)?;
+    if let Element::Procedure(mut elements) = element {
+        environment.enter(|environment| {
+            let mark = environment.history_len();
+            let mut retained = 0;
+            loop {
+                let tail_call = matches!(elements.last(), Some(Element::Function(f)) if f.is_eval());
+                if tail_call {
+                    elements.pop();
+                }
+                for e in elements {
+                    environment.push(e).map_err(|error| error.traced(frame.clone()))?;
+                }
+                if !tail_call {
+                    return Ok(());
+                }
+                let target = environment.pop().map_err(|error| error.traced(frame.clone()))?;
+                let resolved = environment.resolve(&target).map_err(|error| error.traced(frame.clone()))?;
+                if let Element::Procedure(body) = resolved {
+                    frame = frame_name(&target);
+                    elements = body;
+                } else {
+                    return environment.push(resolved);
+                }
+                environment.compact(mark, &mut retained);
+            }
+        })
     } else {
         environment.push(element)
     }
 }
 
+fn frame_name(element: &Element) -> String {
+    match element {
+        Element::Variable(name) => format!("${}", name),
+        element => element.to_string(),
+    }
+}
+
+pub(super) fn call(environment: &mut Environment, procedure: Element) -> Result<(), EvaluationError> {
+    environment.push(procedure)?;
+    eval(environment)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -34,6 +69,14 @@ mod tests {
         assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
     }
 
+    #[test]
+    fn evaluates_symbol_as_itself() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate(":x eval"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Symbol(name)) if name == "x");
+        assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
+    }
+
     #[test]
     fn evaluates_variable_as_value() {
         let mut env = Environment::new();
@@ -50,6 +93,42 @@ mod tests {
         assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
     }
 
+    #[test]
+    fn evaluates_tail_calls_without_growing_call_depth() {
+        let mut env = Environment::new();
+        let longest = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        env.register("probe", {
+            let longest = longest.clone();
+            move |environment| {
+                longest.fetch_max(environment.history_len(), std::sync::atomic::Ordering::Relaxed);
+                Ok(())
+            }
+        });
+        assert_matches!(env.evaluate("{ probe 1 + copy 10000 < $loop {} if . } $loop = 0 $loop ."), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(10000)));
+        assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
+        assert!(longest.load(std::sync::atomic::Ordering::Relaxed) < 500);
+    }
+
+    #[test]
+    fn rolls_back_compacted_tail_calls() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("5 { 1 + copy $n = copy 1000 < $loop { pop pop pop } if . } $loop ="), Ok(()));
+        let error = env.evaluate("0 $loop .").unwrap_err();
+        assert_eq!(error.root(), &EvaluationError::EmptyStack);
+        assert_eq!(env.stack().cloned().collect::<Vec<_>>(), vec![Element::Integer(5)]);
+        assert_eq!(env.get_var("n"), None);
+        assert_matches!(env.get_var("loop"), Some(Element::Procedure(_)));
+    }
+
+    #[test]
+    fn evaluates_tail_call_of_non_procedure_as_itself() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("{ 1 2 . }."), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(2)));
+        assert_matches!(env.pop(), Ok(Element::Integer(1)));
+    }
+
     #[test]
     fn evaluates_procedure_inside_variable() {
         let mut env = Environment::new();
@@ -0,0 +1,132 @@
+use crate::{Element, Environment, EvaluationError};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Pops a path, returning it as the script sees it and as it is found on disk.
+pub(super) fn pop_path(environment: &mut Environment) -> Result<(PathBuf, PathBuf), EvaluationError> {
+    let name = environment.pop_string()?;
+    let path = environment.locate(&name);
+    let real = environment.permissions().path(&path.to_string_lossy())?;
+    Ok((path, real))
+}
+
+pub(super) fn io_error(path: &Path) -> impl FnOnce(io::Error) -> EvaluationError {
+    move |error| EvaluationError::IO(format!("{}: {}", path.display(), error))
+}
+
+pub(super) fn slurp(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let (path, real) = pop_path(environment)?;
+    let contents = fs::read_to_string(real).map_err(io_error(&path))?;
+    environment.push(Element::String(contents))
+}
+
+pub(super) fn write_file(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let (path, real) = pop_path(environment)?;
+    let contents = environment.pop_string()?;
+    fs::write(real, contents).map_err(io_error(&path))
+}
+
+pub(super) fn append_file(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let (path, real) = pop_path(environment)?;
+    let contents = environment.pop_string()?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(real)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(io_error(&path))
+}
+
+pub(super) fn read_lines(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let (path, real) = pop_path(environment)?;
+    let contents = fs::read_to_string(real).map_err(io_error(&path))?;
+    environment.push(Element::Array(contents.lines().map(Element::new_string).collect()))
+}
+
+pub(super) fn exists(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let (_, real) = pop_path(environment)?;
+    environment.push(Element::Boolean(real.exists()))
+}
+
+pub(super) fn list_dir(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let (path, real) = pop_path(environment)?;
+    let mut names = fs::read_dir(real)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+                .collect::<Result<Vec<String>, io::Error>>()
+        })
+        .map_err(io_error(&path))?;
+    names.sort();
+    environment.push(Element::Array(names.into_iter().map(Element::String).collect()))
+}
+
+pub(super) fn remove_file(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let (path, real) = pop_path(environment)?;
+    fs::remove_file(real).map_err(io_error(&path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Capability;
+    use std::assert_matches::assert_matches;
+
+    fn directory(name: &str) -> String {
+        let directory = std::env::temp_dir().join(format!("apn-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn writes_appends_and_reads_files() {
+        let dir = directory("write");
+        let mut env = Environment::new();
+        let program = format!(
+            "\"one\n\" \"{0}/a.txt\" spit \"two\n\" \"{0}/a.txt\" append_file \"{0}/a.txt\" slurp \"{0}/a.txt\" read_lines",
+            dir
+        );
+        assert_matches!(env.evaluate(program), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Array(lines)) if lines == vec![Element::new_string("one"), Element::new_string("two")]);
+        assert_matches!(env.pop(), Ok(Element::String(contents)) if contents == "one\ntwo\n");
+    }
+
+    #[test]
+    fn lists_checks_and_removes_files() {
+        let dir = directory("list");
+        let mut env = Environment::new();
+        let program = format!("\"\" \"{0}/b\" write_file \"\" \"{0}/a\" write_file \"{0}\" list_dir \"{0}/a\" remove_file \"{0}/a\" exists \"{0}/b\" exists", dir);
+        assert_matches!(env.evaluate(program), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Boolean(true)));
+        assert_matches!(env.pop(), Ok(Element::Boolean(false)));
+        assert_matches!(env.pop(), Ok(Element::Array(names)) if names == vec![Element::new_string("a"), Element::new_string("b")]);
+    }
+
+    #[test]
+    fn includes_path_in_io_errors() {
+        let mut env = Environment::new();
+        let error = env.evaluate("\"no/such/file.txt\" slurp").unwrap_err();
+        assert_matches!(error, EvaluationError::IO(message) if message.starts_with("no/such/file.txt: "));
+    }
+
+    #[test]
+    fn requires_filesystem_capability() {
+        let mut env = Environment::builder().deny(Capability::Filesystem).build();
+        assert_matches!(env.evaluate("\"Cargo.toml\" exists"), Err(EvaluationError::PermissionDenied(_)));
+        assert_matches!(env.evaluate("\"x\" \"out.txt\" spit"), Err(EvaluationError::PermissionDenied(_)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_writes_through_dangling_symlinks() {
+        let dir = directory("dangling");
+        let outside = format!("{}.outside.txt", dir);
+        let _ = fs::remove_file(&outside);
+        std::os::unix::fs::symlink(&outside, format!("{}/link", dir)).unwrap();
+        let mut env = Environment::builder().root(&dir).build();
+        assert_matches!(env.evaluate("\"escaped\" \"/link\" spit"), Err(EvaluationError::PermissionDenied(_)));
+        assert!(!Path::new(&outside).exists());
+    }
+}
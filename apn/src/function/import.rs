@@ -0,0 +1,68 @@
+use crate::{Element, Environment, EvaluationError};
+
+fn pop_name(environment: &mut Environment) -> Result<String, EvaluationError> {
+    match environment.pop_value()? {
+        Element::Symbol(name) => Ok(name),
+        element => element.into_string(),
+    }
+}
+
+pub(super) fn import(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let name = pop_name(environment)?;
+    environment.import(&name)
+}
+
+pub(super) fn export(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let names = match environment.pop_value()? {
+        Element::Symbol(name) => vec![name],
+        Element::Array(elements) => elements
+            .into_iter()
+            .map(|e| match e {
+                Element::Symbol(name) => Ok(name),
+                _ => Err(EvaluationError::InvalidStackElements(None)),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        _ => return Err(EvaluationError::InvalidStackElements(None)),
+    };
+    environment.export(names);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches::assert_matches;
+
+    fn environment() -> Environment {
+        Environment::builder().search_path("../programs/modules").build()
+    }
+
+    #[test]
+    fn imports_exported_definitions_under_namespace() {
+        let mut env = environment();
+        assert_matches!(env.evaluate(":math import 3 $math::fourth ."), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(81)));
+        assert_matches!(env.evaluate("$math::square"), Ok(()));
+        assert_matches!(env.pop_value(), Err(EvaluationError::UndefinedVariable(_)));
+        assert_matches!(env.evaluate("$square"), Ok(()));
+        assert_matches!(env.pop_value(), Err(EvaluationError::UndefinedVariable(_)));
+    }
+
+    #[test]
+    fn imports_relative_to_importing_module() {
+        let mut env = environment();
+        assert_matches!(env.evaluate("\"geometry\" import :math import 2 $geometry::tesseract ."), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(32)));
+        assert_matches!(env.evaluate("2 $geometry::math::fourth ."), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(16)));
+    }
+
+    #[test]
+    fn errs_on_missing_and_circular_modules() {
+        let mut env = environment();
+        assert_matches!(env.evaluate(":missing import"), Err(EvaluationError::ModuleNotFound(name)) if name == "missing");
+        let error = env.evaluate(":cycle_a import").unwrap_err();
+        assert_matches!(error.root(), EvaluationError::CircularImport(name) if name == "cycle_a");
+        assert_eq!(env.vars().count(), 0);
+    }
+}
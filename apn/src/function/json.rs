@@ -0,0 +1,404 @@
+use crate::element::Key;
+use crate::{Element, Environment, EvaluationError};
+use std::collections::BTreeMap;
+
+const NULL: &str = "null";
+
+pub(super) fn json_parse(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let text = environment.pop_string()?;
+    let chars = text.chars().collect::<Vec<char>>();
+    let mut parser = Parser { chars: &chars, index: 0, depth: 0, max_depth: environment.max_call_depth() };
+    let element = parser.value()?;
+    parser.skip_whitespace();
+    if parser.index < chars.len() {
+        return Err(parser.unexpected());
+    }
+    environment.push(element)
+}
+
+pub(super) fn json_stringify(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let element = environment.pop_value()?;
+    let mut json = String::new();
+    stringify(&element, &mut json, 0, environment.max_call_depth())?;
+    environment.push(Element::String(json))
+}
+
+fn too_deep(max_depth: usize) -> EvaluationError {
+    EvaluationError::Json(format!("nesting deeper than {} levels", max_depth))
+}
+
+fn stringify(element: &Element, json: &mut String, depth: usize, max_depth: usize) -> Result<(), EvaluationError> {
+    if matches!(element, Element::Array(_) | Element::Map(_)) && depth == max_depth {
+        return Err(too_deep(max_depth));
+    }
+    match element {
+        Element::Boolean(b) => json.push_str(&b.to_string()),
+        Element::Integer(i) => json.push_str(&i.to_string()),
+        Element::Float(f) if f.is_finite() => json.push_str(&format!("{:?}", f)),
+        Element::Float(f) => return Err(EvaluationError::Json(format!("{} has no JSON form", f))),
+        Element::Symbol(s) if s == NULL => json.push_str(NULL),
+        Element::Symbol(s) => quote(s, json),
+        Element::Char(_) | Element::String(_) => quote(&element.to_text(), json),
+        Element::Array(elements) => {
+            json.push('[');
+            for (i, e) in elements.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                stringify(e, json, depth + 1, max_depth)?;
+            }
+            json.push(']');
+        }
+        Element::Map(entries) => {
+            json.push('{');
+            for (i, (k, v)) in entries.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                match k {
+                    Key::Symbol(s) => quote(s, json),
+                    k => quote(&Element::from(k.clone()).to_text(), json),
+                }
+                json.push(':');
+                stringify(v, json, depth + 1, max_depth)?;
+            }
+            json.push('}');
+        }
+        element @ (Element::Variable(_) | Element::Function(_) | Element::Procedure(_)) => {
+            return Err(EvaluationError::Json(format!("{} has no JSON form", element.type_name())));
+        }
+    }
+    Ok(())
+}
+
+fn quote(text: &str, json: &mut String) {
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+struct Parser<'a> {
+    chars: &'a [char],
+    index: usize,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Parser<'_> {
+    fn unexpected(&self) -> EvaluationError {
+        match self.chars.get(self.index) {
+            Some(c) => EvaluationError::Json(format!("unexpected '{}' at {}", c, self.index)),
+            None => EvaluationError::Json("unexpected end of input".to_string()),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.chars.get(self.index), Some(' ' | '\t' | '\n' | '\r')) {
+            self.index += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), EvaluationError> {
+        if self.chars.get(self.index) == Some(&c) {
+            self.index += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn literal(&mut self, word: &str, element: Element) -> Result<Element, EvaluationError> {
+        for c in word.chars() {
+            self.expect(c)?;
+        }
+        Ok(element)
+    }
+
+    fn value(&mut self) -> Result<Element, EvaluationError> {
+        self.skip_whitespace();
+        match self.chars.get(self.index) {
+            Some('{') => self.nested(Self::object),
+            Some('[') => self.nested(Self::array),
+            Some('"') => Ok(Element::String(self.string()?)),
+            Some('t') => self.literal("true", Element::Boolean(true)),
+            Some('f') => self.literal("false", Element::Boolean(false)),
+            Some('n') => self.literal(NULL, Element::Symbol(NULL.to_string())),
+            Some('-' | '0'..='9') => self.number(),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Element, EvaluationError>) -> Result<Element, EvaluationError> {
+        if self.depth == self.max_depth {
+            return Err(too_deep(self.max_depth));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn object(&mut self) -> Result<Element, EvaluationError> {
+        self.expect('{')?;
+        let mut entries = BTreeMap::new();
+        self.skip_whitespace();
+        if self.chars.get(self.index) == Some(&'}') {
+            self.index += 1;
+            return Ok(Element::Map(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            entries.insert(Key::String(key), self.value()?);
+            self.skip_whitespace();
+            match self.chars.get(self.index) {
+                Some(',') => self.index += 1,
+                Some('}') => {
+                    self.index += 1;
+                    return Ok(Element::Map(entries));
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Element, EvaluationError> {
+        self.expect('[')?;
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if self.chars.get(self.index) == Some(&']') {
+            self.index += 1;
+            return Ok(Element::Array(elements));
+        }
+        loop {
+            elements.push(self.value()?);
+            self.skip_whitespace();
+            match self.chars.get(self.index) {
+                Some(',') => self.index += 1,
+                Some(']') => {
+                    self.index += 1;
+                    return Ok(Element::Array(elements));
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, EvaluationError> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            let c = *self.chars.get(self.index).ok_or_else(|| self.unexpected())?;
+            self.index += 1;
+            match c {
+                '"' => return Ok(string),
+                '\\' => string.push(self.escape()?),
+                c if (c as u32) < 0x20 => {
+                    self.index -= 1;
+                    return Err(self.unexpected());
+                }
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, EvaluationError> {
+        let c = *self.chars.get(self.index).ok_or_else(|| self.unexpected())?;
+        self.index += 1;
+        match c {
+            '"' | '\\' | '/' => Ok(c),
+            'b' => Ok('\u{8}'),
+            'f' => Ok('\u{c}'),
+            'n' => Ok('\n'),
+            'r' => Ok('\r'),
+            't' => Ok('\t'),
+            'u' => {
+                let start = self.index - 2;
+                let invalid = || EvaluationError::Json(format!("invalid escape at {}", start));
+                let high = self.hex()?;
+                if (0xd800..0xdc00).contains(&high) {
+                    self.expect('\\')?;
+                    self.expect('u')?;
+                    let low = self.hex()?;
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(invalid());
+                    }
+                    char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)).ok_or_else(invalid)
+                } else {
+                    char::from_u32(high).ok_or_else(invalid)
+                }
+            }
+            _ => {
+                self.index -= 1;
+                Err(self.unexpected())
+            }
+        }
+    }
+
+    fn hex(&mut self) -> Result<u32, EvaluationError> {
+        let digits = self.chars.get(self.index..self.index + 4).ok_or_else(|| self.unexpected())?;
+        if let Some(offset) = digits.iter().position(|c| !c.is_ascii_hexdigit()) {
+            self.index += offset;
+            return Err(self.unexpected());
+        }
+        self.index += 4;
+        Ok(digits.iter().fold(0, |code, c| code * 16 + c.to_digit(16).unwrap()))
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.chars.get(self.index) == Some(&c);
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    fn digits(&mut self) -> Result<(), EvaluationError> {
+        let start = self.index;
+        while matches!(self.chars.get(self.index), Some('0'..='9')) {
+            self.index += 1;
+        }
+        if self.index == start {
+            Err(self.unexpected())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn number(&mut self) -> Result<Element, EvaluationError> {
+        let start = self.index;
+        self.eat('-');
+        if !self.eat('0') {
+            self.digits()?;
+        }
+        let mut integral = true;
+        if self.eat('.') {
+            integral = false;
+            self.digits()?;
+        }
+        if self.eat('e') || self.eat('E') {
+            integral = false;
+            if !self.eat('+') {
+                self.eat('-');
+            }
+            self.digits()?;
+        }
+        let text = self.chars[start..self.index].iter().collect::<String>();
+        let number = if integral {
+            text.parse::<i64>().map(Element::Integer).ok()
+        } else {
+            None
+        };
+        number
+            .or_else(|| text.parse::<f64>().ok().filter(|f| f.is_finite()).map(Element::Float))
+            .ok_or_else(|| EvaluationError::Json(format!("invalid number {} at {}", text, start)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Limits;
+    use std::assert_matches::assert_matches;
+
+    fn parse(env: &mut Environment, json: &str) -> Result<Element, EvaluationError> {
+        env.push(Element::new_string(json))?;
+        json_parse(env)?;
+        env.pop()
+    }
+
+    #[test]
+    fn parses_nested_json() {
+        let mut env = Environment::new();
+        let element = parse(&mut env, r#" {"a": [1, -2.5e1, true, null], "b": {"c": "x\"é😀"}, "d": []} "#);
+        assert_eq!(
+            element.unwrap().to_string(),
+            "#{ \"a\" [ 1 -25 true :null ] \"b\" #{ \"c\" \"x\"é😀\" } \"d\" [ ] }"
+        );
+        assert_eq!(parse(&mut env, "12345678901234567890"), Ok(Element::Float(12345678901234567890.0)));
+    }
+
+    #[test]
+    fn errs_on_invalid_json() {
+        let mut env = Environment::new();
+        assert_eq!(parse(&mut env, "[1, 2"), Err(EvaluationError::Json("unexpected end of input".to_string())));
+        assert_eq!(parse(&mut env, "{\"a\" 1}"), Err(EvaluationError::Json("unexpected '1' at 5".to_string())));
+        assert_eq!(parse(&mut env, "[1] x"), Err(EvaluationError::Json("unexpected 'x' at 4".to_string())));
+        assert_matches!(parse(&mut env, "1.2.3"), Err(EvaluationError::Json(_)));
+        assert_eq!(parse(&mut env, "\"\\u+041\""), Err(EvaluationError::Json("unexpected '+' at 3".to_string())));
+    }
+
+    #[test]
+    fn errs_on_numbers_outside_json_grammar() {
+        let mut env = Environment::new();
+        for json in ["01", "-", "+1", ".5", "1.", "1e", "1e+", "--1", "1e999"] {
+            assert_matches!(parse(&mut env, json), Err(EvaluationError::Json(_)), "{}", json);
+        }
+        assert_eq!(parse(&mut env, "-0"), Ok(Element::Integer(0)));
+        assert_eq!(parse(&mut env, "10"), Ok(Element::Integer(10)));
+        assert_eq!(parse(&mut env, "-1.5E+2"), Ok(Element::Float(-150.0)));
+        assert_eq!(parse(&mut env, "0e-1"), Ok(Element::Float(0.0)));
+    }
+
+    #[test]
+    fn decodes_surrogate_pairs() {
+        let mut env = Environment::new();
+        assert_eq!(parse(&mut env, r#""\ud83d\ude00\u00e9""#), Ok(Element::new_string("😀é")));
+        let error = Err(EvaluationError::Json("invalid escape at 1".to_string()));
+        assert_eq!(parse(&mut env, r#""\ud800\u0041""#), error);
+        assert_eq!(parse(&mut env, r#""\udc00""#), error);
+        assert_matches!(parse(&mut env, r#""\ud800x""#), Err(EvaluationError::Json(_)));
+    }
+
+    #[test]
+    fn errs_on_nesting_deeper_than_call_depth() {
+        let mut env = Environment::new();
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert_matches!(parse(&mut env, &nested(256)), Ok(_));
+        let error = Err(EvaluationError::Json("nesting deeper than 256 levels".to_string()));
+        assert_eq!(parse(&mut env, &nested(257)), error);
+        assert_eq!(parse(&mut env, &"[".repeat(200000)), error);
+        assert_eq!(parse(&mut env, &"{\"a\":".repeat(200000)), error);
+        let element = parse(&mut env, &nested(20)).unwrap();
+        let mut shallow = Environment::with_limits(Limits { max_call_depth: 10, ..Limits::default() });
+        assert_matches!(shallow.push(element), Ok(()));
+        assert_eq!(shallow.evaluate("json_stringify"), Err(EvaluationError::Json("nesting deeper than 10 levels".to_string())));
+        assert_matches!(shallow.evaluate("pop [[1]] json_stringify"), Ok(()));
+    }
+
+    #[test]
+    fn stringifies_elements() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("#{ :a [1 2.0 'c'] \"b\" :null 3 #{ } true false } json_stringify"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::String(json)) if json == r#"{"true":false,"3":{},"b":null,"a":[1,2.0,"c"]}"#);
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut env = Environment::new();
+        let json = r#"{"list":[1,2.5,"three\n",null,{"nested":true}]}"#;
+        env.push(Element::new_string(json)).unwrap();
+        assert_matches!(env.evaluate("json_parse json_stringify"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::String(s)) if s == json);
+    }
+
+    #[test]
+    fn errs_on_values_without_json_form() {
+        let mut env = Environment::new();
+        let error = env.evaluate("[1 { 2 }] json_stringify").unwrap_err();
+        assert_eq!(error, EvaluationError::Json("procedure has no JSON form".to_string()));
+        assert_eq!(error.to_string(), "JSON error: procedure has no JSON form");
+    }
+}
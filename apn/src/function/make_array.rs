@@ -3,7 +3,7 @@ use crate::{Element, Environment, EvaluationError};
 pub(super) fn make_array(environment: &mut Environment) -> Result<(), EvaluationError> {
     if let Ok(Element::Integer(n)) = environment.pop() {
         if n <= 0 {
-            Err(EvaluationError::InvalidStackElements)
+            Err(EvaluationError::InvalidStackElements(None))
         } else {
             let mut array = Vec::new();
             for _ in 0..n {
@@ -13,7 +13,7 @@ pub(super) fn make_array(environment: &mut Environment) -> Result<(), Evaluation
             environment.push(Element::Array(array))
         }
     } else {
-        Err(EvaluationError::InvalidStackElements)
+        Err(EvaluationError::InvalidStackElements(None))
     }
 }
 
@@ -43,7 +43,7 @@ mod tests {
     fn errors_on_negative_integer() {
         let mut env = Environment::new();
         env.evaluate("10 20").unwrap();
-        assert_matches!(env.evaluate("-1 make_array"), Err(EvaluationError::InvalidStackElements));
-        assert_matches!(env.evaluate("0 make_array"), Err(EvaluationError::InvalidStackElements));
+        assert_matches!(env.evaluate("-1 make_array"), Err(EvaluationError::InvalidStackElements(_)));
+        assert_matches!(env.evaluate("0 make_array"), Err(EvaluationError::InvalidStackElements(_)));
     }
 }
\ No newline at end of file
@@ -1,31 +1,41 @@
 use crate::{Element, Environment, EvaluationError};
-use crate::function::eval::eval;
 use crate::function::Function;
-use crate::function::make_array::make_array;
+use std::collections::BTreeMap;
 
 pub(super) fn map(environment: &mut Environment) -> Result<(), EvaluationError> {
     if let Element::Procedure(proc) = environment.pop_value()? {
-        if let Element::Array(array) = environment.pop_value()? {
-            let len = array.len();
-            for e in array {
-                environment.push(e)?;
-                environment.push(Element::Procedure(proc.clone()))?;
-                environment.push(Element::Function(Function("eval", eval)))?;
+        match environment.pop_value()? {
+            Element::Map(entries) => {
+                let mut result = BTreeMap::new();
+                for (k, v) in entries {
+                    environment.push(v)?;
+                    environment.push(Element::Procedure(proc.clone()))?;
+                    environment.push(Element::Function(Function::try_from(".").unwrap()))?;
+                    result.insert(k, environment.pop()?);
+                }
+                environment.push(Element::Map(result))
+            }
+            element => {
+                let array = element.into_array()?;
+                let len = array.len();
+                for e in array {
+                    environment.push(e)?;
+                    environment.push(Element::Procedure(proc.clone()))?;
+                    environment.push(Element::Function(Function::try_from(".").unwrap()))?;
+                }
+                environment.push(Element::Integer(len as i64))?;
+                environment.push(Element::Function(Function::try_from("make_array").unwrap()))
             }
-            environment.push(Element::Integer(len as i64))?;
-            environment.push(Element::Function(Function("make_array", make_array)))
-        } else {
-            Err(EvaluationError::InvalidStackElements)
         }
     } else {
-        Err(EvaluationError::InvalidStackElements)
+        Err(EvaluationError::InvalidStackElements(None))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use std::assert_matches::assert_matches;
-    use crate::Element;
+    use crate::{Element, Key};
     use super::*;
 
     #[test]
@@ -40,4 +50,14 @@ mod tests {
         matches!(array[4], Element::Integer(10)));
         assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
     }
+
+    #[test]
+    fn applies_procedure_to_each_value_in_map() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("#{ \"a\" 1 \"b\" 2 } {10 *} map"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Map(map)) if
+        map.get(&Key::String("a".to_string())) == Some(&Element::Integer(10)) &&
+        map.get(&Key::String("b".to_string())) == Some(&Element::Integer(20)));
+        assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
+    }
 }
\ No newline at end of file
@@ -8,10 +8,10 @@ pub(super) fn modulo(environment: &mut Environment) -> Result<(), EvaluationErro
         if let Element::Integer(a) = environment.pop_value()? {
             environment.push(Element::Integer(a % b))
         } else {
-            Err(EvaluationError::InvalidStackElements)
+            Err(EvaluationError::InvalidStackElements(None))
         }
     } else {
-        Err(EvaluationError::InvalidStackElements)
+        Err(EvaluationError::InvalidStackElements(None))
     }
 }
 
@@ -9,7 +9,7 @@ pub(super) fn multiply(environment: &mut Environment) -> Result<(), EvaluationEr
         (Integer(a), Float(b)) => Float(a as f64 * b),
         (Float(a), Integer(b)) => Float(a * b as f64),
         (Float(a), Float(b)) => Float(a * b),
-        _ => return Err(EvaluationError::FunctionNotApplicable),
+        _ => return Err(EvaluationError::FunctionNotApplicable(None)),
     })
 }
 
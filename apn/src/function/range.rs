@@ -3,12 +3,14 @@ use crate::{Element, Environment, EvaluationError};
 pub(super) fn range(environment: &mut Environment) -> Result<(), EvaluationError> {
     if let Element::Integer(b) = environment.pop_value()? {
         if let Element::Integer(a) = environment.pop_value()? {
+            let count = usize::try_from((b as i128 - a as i128 + 1).max(0)).unwrap_or(usize::MAX);
+            environment.reserve(count.saturating_add(1).saturating_mul(size_of::<Element>()))?;
             environment.push(Element::Array((a..=b).map(Element::Integer).collect()))
         } else {
-            Err(EvaluationError::InvalidStackElements)
+            Err(EvaluationError::InvalidStackElements(None))
         }
     } else {
-        Err(EvaluationError::InvalidStackElements)
+        Err(EvaluationError::InvalidStackElements(None))
     }
 }
 
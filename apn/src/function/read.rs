@@ -1,11 +1,12 @@
+use crate::function::file::{io_error, pop_path};
 use crate::{Environment, EvaluationError};
 use std::io::Error;
 use std::fs;
 
 pub(super) fn read(environment: &mut Environment) -> Result<(), EvaluationError> {
-    let filename = environment.pop()?.as_string()?;
-    let contents = fs::read_to_string(filename)?;
-    environment.evaluate(contents)
+    let (path, real) = pop_path(environment)?;
+    let contents = fs::read_to_string(real).map_err(io_error(&path))?;
+    environment.evaluate_file(path, contents)
 }
 
 impl From<Error> for EvaluationError {
@@ -17,7 +18,7 @@ impl From<Error> for EvaluationError {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Element;
+    use crate::{Capability, Element};
     use std::assert_matches::assert_matches;
 
     #[test]
@@ -28,4 +29,37 @@ mod tests {
         assert_matches!(env.pop(), Ok(Element::Integer(3)));
         assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
     }
+
+    #[test]
+    fn reads_relative_to_current_file() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("\"../programs/include/outer.apn\" read 2 $inc ."), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(3)));
+    }
+
+    #[test]
+    fn reports_include_chain_of_failing_read() {
+        let mut env = Environment::new();
+        let error = env.evaluate("\"../programs/include/broken.apn\" read").unwrap_err();
+        assert_matches!(error.root(), EvaluationError::IO(message) if message.starts_with("../programs/include/missing.apn: "));
+        assert_eq!(
+            error.trace(),
+            &["file ../programs/include/failing.apn".to_string(), "file ../programs/include/broken.apn".to_string()]
+        );
+    }
+
+    #[test]
+    fn reads_only_with_filesystem_capability() {
+        let mut env = Environment::builder().deny(Capability::Filesystem).build();
+        let error = env.evaluate("\"../programs/inc.apn\" read").unwrap_err();
+        assert_eq!(error, EvaluationError::PermissionDenied("filesystem access".to_string()));
+    }
+
+    #[test]
+    fn reads_inside_root_directory_only() {
+        let mut env = Environment::builder().sandboxed().allow(Capability::Filesystem).root("../programs").build();
+        assert_matches!(env.evaluate("\"/inc.apn\" read 2 $inc ."), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(3)));
+        assert_matches!(env.evaluate("\"../apn/Cargo.toml\" read"), Err(EvaluationError::PermissionDenied(_)));
+    }
 }
\ No newline at end of file
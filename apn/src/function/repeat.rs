@@ -5,25 +5,29 @@ use crate::function::eval::eval;
 pub(super) fn repeat(environment: &mut Environment) -> Result<(), EvaluationError> {
     if let Element::Integer(count) = environment.pop_value()? {
         let element = environment.pop()?;
+        environment.reserve(element.size().saturating_mul(count.max(0) as usize))?;
         for _ in 0..count {
             environment.push(element.clone())?;
         }
         Ok(())
     } else {
-        Err(EvaluationError::InvalidStackElements)
+        Err(EvaluationError::InvalidStackElements(None))
     }
 }
 
 pub(super) fn repeat_eval(environment: &mut Environment) -> Result<(), EvaluationError> {
     if let Element::Integer(count) = environment.pop_value()? {
         let element = environment.pop()?;
+        let mark = environment.history_len();
+        let mut retained = 0;
         for _ in 0..count {
             environment.push(element.clone())?;
             eval(environment)?;
+            environment.compact(mark, &mut retained);
         }
         Ok(())
     } else {
-        Err(EvaluationError::InvalidStackElements)
+        Err(EvaluationError::InvalidStackElements(None))
     }
 }
 
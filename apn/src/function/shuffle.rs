@@ -0,0 +1,149 @@
+use crate::{Element, Environment, EvaluationError};
+
+fn pop_count(environment: &mut Environment) -> Result<usize, EvaluationError> {
+    match environment.pop_value()? {
+        Element::Integer(n) if n >= 0 => Ok(n as usize),
+        _ => Err(EvaluationError::InvalidStackElements(None)),
+    }
+}
+
+pub(super) fn over(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let b = environment.pop()?;
+    let a = environment.pop()?;
+    environment.push(a.clone())?;
+    environment.push(b)?;
+    environment.push(a)
+}
+
+pub(super) fn rot(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let c = environment.pop()?;
+    let b = environment.pop()?;
+    let a = environment.pop()?;
+    environment.push(b)?;
+    environment.push(c)?;
+    environment.push(a)
+}
+
+pub(super) fn reverse_rot(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let c = environment.pop()?;
+    let b = environment.pop()?;
+    let a = environment.pop()?;
+    environment.push(c)?;
+    environment.push(a)?;
+    environment.push(b)
+}
+
+pub(super) fn nip(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let b = environment.pop()?;
+    environment.pop()?;
+    environment.push(b)
+}
+
+pub(super) fn tuck(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let b = environment.pop()?;
+    let a = environment.pop()?;
+    environment.push(b.clone())?;
+    environment.push(a)?;
+    environment.push(b)
+}
+
+pub(super) fn pick(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let n = pop_count(environment)?;
+    let mut above = Vec::new();
+    for _ in 0..=n {
+        above.push(environment.pop()?);
+    }
+    let picked = above.last().unwrap().clone();
+    for e in above.into_iter().rev() {
+        environment.push(e)?;
+    }
+    environment.push(picked)
+}
+
+pub(super) fn roll(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let n = pop_count(environment)?;
+    let mut above = Vec::new();
+    for _ in 0..=n {
+        above.push(environment.pop()?);
+    }
+    let rolled = above.pop().unwrap();
+    for e in above.into_iter().rev() {
+        environment.push(e)?;
+    }
+    environment.push(rolled)
+}
+
+pub(super) fn dup2(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let b = environment.pop()?;
+    let a = environment.pop()?;
+    environment.push(a.clone())?;
+    environment.push(b.clone())?;
+    environment.push(a)?;
+    environment.push(b)
+}
+
+pub(super) fn drop2(environment: &mut Environment) -> Result<(), EvaluationError> {
+    environment.pop()?;
+    environment.pop()?;
+    Ok(())
+}
+
+pub(super) fn depth(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let depth = environment.stack_len();
+    environment.push(Element::Integer(depth as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches::assert_matches;
+
+    fn integers(env: &Environment) -> Vec<i64> {
+        env.stack()
+            .map(|e| if let Element::Integer(i) = e { *i } else { panic!("not an integer") })
+            .collect()
+    }
+
+    #[test]
+    fn swaps_and_copies_under_top() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("1 2 swap 3 over"), Ok(()));
+        assert_eq!(integers(&env), vec![2, 1, 3, 1]);
+    }
+
+    #[test]
+    fn rotates_three_elements_both_ways() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("1 2 3 rot"), Ok(()));
+        assert_eq!(integers(&env), vec![2, 3, 1]);
+        assert_matches!(env.evaluate("-rot"), Ok(()));
+        assert_eq!(integers(&env), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn nips_and_tucks() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("1 2 nip 3 tuck"), Ok(()));
+        assert_eq!(integers(&env), vec![3, 2, 3]);
+    }
+
+    #[test]
+    fn picks_and_rolls_nth_element() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("1 2 3 2 pick"), Ok(()));
+        assert_eq!(integers(&env), vec![1, 2, 3, 1]);
+        assert_matches!(env.evaluate("2 roll"), Ok(()));
+        assert_eq!(integers(&env), vec![1, 3, 1, 2]);
+        assert_matches!(env.evaluate("4 pick"), Err(EvaluationError::EmptyStack));
+        assert_eq!(integers(&env), vec![1, 3, 1, 2]);
+    }
+
+    #[test]
+    fn copies_and_drops_pairs() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("1 2 dup2"), Ok(()));
+        assert_eq!(integers(&env), vec![1, 2, 1, 2]);
+        assert_matches!(env.evaluate("drop2 drop2 depth"), Ok(()));
+        assert_eq!(integers(&env), vec![0]);
+    }
+}
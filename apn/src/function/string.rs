@@ -0,0 +1,158 @@
+use crate::{Element, Environment, EvaluationError};
+
+pub(super) fn split(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let separator = environment.pop_string()?;
+    let string = environment.pop_string()?;
+    let parts = if separator.is_empty() {
+        string.chars().map(|c| Element::new_string(&c.to_string())).collect()
+    } else {
+        string.split(separator.as_str()).map(Element::new_string).collect()
+    };
+    environment.push(Element::Array(parts))
+}
+
+pub(super) fn join(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let separator = environment.pop_string()?;
+    if let Ok(array) = environment.pop_value()?.into_array() {
+        let parts = array
+            .iter()
+            .map(Element::as_string)
+            .collect::<Result<Vec<_>, EvaluationError>>()?;
+        environment.push(Element::new_string(&parts.join(&separator)))
+    } else {
+        Err(EvaluationError::InvalidStackElements(None))
+    }
+}
+
+pub(super) fn trim(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let string = environment.pop_string()?;
+    environment.push(Element::new_string(string.trim()))
+}
+
+pub(super) fn upper(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let string = environment.pop_string()?;
+    environment.push(Element::new_string(&string.to_uppercase()))
+}
+
+pub(super) fn lower(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let string = environment.pop_string()?;
+    environment.push(Element::new_string(&string.to_lowercase()))
+}
+
+pub(super) fn starts_with(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let prefix = environment.pop_string()?;
+    let string = environment.pop_string()?;
+    environment.push(Element::Boolean(string.starts_with(&prefix)))
+}
+
+pub(super) fn ends_with(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let suffix = environment.pop_string()?;
+    let string = environment.pop_string()?;
+    environment.push(Element::Boolean(string.ends_with(&suffix)))
+}
+
+pub(super) fn find(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let needle = environment.pop_string()?;
+    let string = environment.pop_string()?;
+    let index = match string.find(&needle) {
+        Some(byte_index) => string[..byte_index].chars().count() as i64,
+        None => -1,
+    };
+    environment.push(Element::Integer(index))
+}
+
+pub(super) fn replace(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let to = environment.pop_string()?;
+    let from = environment.pop_string()?;
+    let string = environment.pop_string()?;
+    if from.is_empty() {
+        return Err(EvaluationError::FunctionNotApplicable(None));
+    }
+    environment.push(Element::new_string(&string.replace(&from, &to)))
+}
+
+pub(super) fn chars(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let string = environment.pop_string()?;
+    environment.push(Element::Array(string.chars().map(Element::Char).collect()))
+}
+
+pub(super) fn lines(environment: &mut Environment) -> Result<(), EvaluationError> {
+    let string = environment.pop_string()?;
+    environment.push(Element::Array(string.lines().map(Element::new_string).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches::assert_matches;
+
+    fn pop_strings(env: &mut Environment) -> Vec<String> {
+        if let Ok(Element::Array(array)) = env.pop() {
+            array.iter().map(|e| e.as_string().unwrap().into_owned()).collect()
+        } else {
+            panic!("expected an array on top of the stack")
+        }
+    }
+
+    #[test]
+    fn splits_string_on_separator() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("\"a,b,,c\" \",\" split"), Ok(()));
+        assert_eq!(pop_strings(&mut env), vec!["a", "b", "", "c"]);
+        assert_matches!(env.pop(), Err(EvaluationError::EmptyStack));
+    }
+
+    #[test]
+    fn joins_strings_with_separator() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("[\"a\" \"b\" \"c\"] \", \" join"), Ok(()));
+        assert_eq!(env.pop_string(), Ok("a, b, c".to_string()));
+    }
+
+    #[test]
+    fn trims_and_changes_case() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("\"  Hello \" trim copy upper rotate lower"), Ok(()));
+        assert_eq!(env.pop_string(), Ok("hello".to_string()));
+        assert_eq!(env.pop_string(), Ok("HELLO".to_string()));
+    }
+
+    #[test]
+    fn checks_prefix_and_suffix() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("\"hello\" \"he\" starts_with \"hello\" \"he\" ends_with"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Boolean(false)));
+        assert_matches!(env.pop(), Ok(Element::Boolean(true)));
+    }
+
+    #[test]
+    fn finds_character_index_of_substring() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("\"héllo\" \"llo\" find \"hello\" \"x\" find"), Ok(()));
+        assert_matches!(env.pop(), Ok(Element::Integer(-1)));
+        assert_matches!(env.pop(), Ok(Element::Integer(2)));
+    }
+
+    #[test]
+    fn replaces_all_occurrences() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("\"hello\" \"l\" \"L\" replace"), Ok(()));
+        assert_eq!(env.pop_string(), Ok("heLLo".to_string()));
+    }
+
+    #[test]
+    fn splits_into_chars_and_lines() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("\"ab\" chars \"a\nb\" lines"), Ok(()));
+        assert_eq!(pop_strings(&mut env), vec!["a", "b"]);
+        assert_matches!(env.pop(), Ok(Element::Array(array)) if array == vec![Element::Char('a'), Element::Char('b')]);
+    }
+
+    #[test]
+    fn errs_on_mixed_arrays() {
+        let mut env = Environment::new();
+        assert_matches!(env.evaluate("['a' 1] trim"), Err(EvaluationError::NotAString));
+        assert_matches!(env.evaluate("1 upper"), Err(EvaluationError::NotAString));
+        assert_matches!(env.evaluate("[\"a\" 1] \",\" join"), Err(EvaluationError::NotAString));
+    }
+}
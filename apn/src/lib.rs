@@ -4,6 +4,10 @@ mod element;
 mod environment;
 mod function;
 mod parser;
+mod permissions;
 
-pub use environment::{Environment, EvaluationError};
-pub use element::Element;
+pub use environment::call::{Arguments, Results};
+pub use environment::{Environment, EnvironmentBuilder, EvaluationError, Limits, Mismatch, Snapshot};
+pub use permissions::{Capability, Permissions};
+pub use element::{Element, Key};
+pub use function::HostFunction;
@@ -1,18 +1,27 @@
-use crate::element::Element;
+use crate::element::{Element, Key};
+use crate::function::Function;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
 
-pub(super) fn parse(input: impl Into<String>) -> Result<Vec<Element>, ParserError> {
+/// Parses `input`, resolving words registered in `functions` before built-in ones.
+pub(super) fn parse(
+    input: impl Into<String>,
+    functions: &HashMap<String, Function>,
+) -> Result<Vec<Element>, ParserError> {
     let chars = input.into().chars().collect::<Vec<char>>();
-    Ok(parse_chars(&chars, ParserState::Empty, 0)?.0)
+    Ok(parse_chars(&chars, functions, ParserState::Empty, 0)?.0)
 }
 
 enum ParserState {
     Empty,
     Array,
     Procedure,
+    Map,
 }
 
 fn parse_chars(
     chars: &[char],
+    functions: &HashMap<String, Function>,
     state: ParserState,
     index: usize,
 ) -> Result<(Vec<Element>, usize), ParserError> {
@@ -22,7 +31,7 @@ fn parse_chars(
         match chars[index] {
             '"' => {
                 let (string, new_index) = read_string(chars, index + 1)?;
-                elements.push(Element::Array(string));
+                elements.push(Element::String(string));
                 index = new_index;
             }
             '\'' => {
@@ -42,8 +51,13 @@ fn parse_chars(
                 elements.push(element);
                 index = new_index;
             }
+            ':' => {
+                let (element, new_index) = read_symbol(chars, index + 1)?;
+                elements.push(element);
+                index = new_index;
+            }
             '[' => {
-                let (array, new_index) = parse_chars(chars, ParserState::Array, index + 1)?;
+                let (array, new_index) = parse_chars(chars, functions, ParserState::Array, index + 1)?;
                 elements.push(Element::Array(array));
                 index = new_index;
             }
@@ -51,11 +65,12 @@ fn parse_chars(
                 return match state {
                     ParserState::Empty => Err(ParserError::NotInsideArray),
                     ParserState::Procedure => Err(ParserError::NotInsideArray),
+                    ParserState::Map => Err(ParserError::NotInsideArray),
                     ParserState::Array => Ok((elements, index + 1)),
                 }
             }
             '{' => {
-                let (procedure, new_index) = parse_chars(chars, ParserState::Procedure, index + 1)?;
+                let (procedure, new_index) = parse_chars(chars, functions, ParserState::Procedure, index + 1)?;
                 elements.push(Element::Procedure(procedure));
                 index = new_index;
             }
@@ -64,13 +79,19 @@ fn parse_chars(
                     ParserState::Empty => Err(ParserError::NotInsideProcedure),
                     ParserState::Array => Err(ParserError::NotInsideProcedure),
                     ParserState::Procedure => Ok((elements, index + 1)),
+                    ParserState::Map => Ok((elements, index + 1)),
                 }
             }
+            '#' if chars.get(index + 1) == Some(&'{') => {
+                let (entries, new_index) = parse_chars(chars, functions, ParserState::Map, index + 2)?;
+                elements.push(read_map(entries)?);
+                index = new_index;
+            }
             ' ' | '\t' | '\n' | '\r' => {
                 index += 1;
             }
             _ => {
-                let (element, new_index) = read_element(chars, index)?;
+                let (element, new_index) = read_element(chars, functions, index)?;
                 elements.push(element);
                 index = new_index;
             }
@@ -79,7 +100,7 @@ fn parse_chars(
     Ok((elements, index))
 }
 
-fn read_string(chars: &[char], index: usize) -> Result<(Vec<Element>, usize), ParserError> {
+fn read_string(chars: &[char], index: usize) -> Result<(String, usize), ParserError> {
     let mut max_index = index;
     let mut escaped = false;
     while max_index < chars.len() {
@@ -97,10 +118,24 @@ fn read_string(chars: &[char], index: usize) -> Result<(Vec<Element>, usize), Pa
             }
         }
     }
-    Ok((chars[index..max_index].iter().cloned().map(Element::Char).collect(), max_index + 1))
+    Ok((chars[index..max_index].iter().collect(), max_index + 1))
 }
 
-fn read_variable(chars: &[char], index: usize) -> Result<(Element, usize), ParserError> {
+fn read_map(elements: Vec<Element>) -> Result<Element, ParserError> {
+    if !elements.len().is_multiple_of(2) {
+        return Err(ParserError::MissingMapValue);
+    }
+    let mut entries = BTreeMap::new();
+    let mut elements = elements.into_iter();
+    while let (Some(key), Some(value)) = (elements.next(), elements.next()) {
+        let display = key.to_string();
+        let key = Key::try_from(key).map_err(|_| ParserError::InvalidMapKey(display))?;
+        entries.insert(key, value);
+    }
+    Ok(Element::Map(entries))
+}
+
+fn read_name(chars: &[char], index: usize) -> (String, usize) {
     let mut max_index = index;
     while max_index < chars.len() {
         match chars[max_index] {
@@ -108,18 +143,47 @@ fn read_variable(chars: &[char], index: usize) -> Result<(Element, usize), Parse
             _ => break,
         }
     }
-    Ok((Element::Variable(chars[index..max_index].iter().collect()), max_index))
+    (chars[index..max_index].iter().collect(), max_index)
 }
 
-fn read_element(chars: &[char], index: usize) -> Result<(Element, usize), ParserError> {
+fn read_variable(chars: &[char], index: usize) -> Result<(Element, usize), ParserError> {
+    let (mut name, mut max_index) = read_name(chars, index);
+    while chars.get(max_index..max_index + 2) == Some(&[':', ':']) {
+        let (segment, new_index) = read_name(chars, max_index + 2);
+        if segment.is_empty() {
+            break;
+        }
+        name = format!("{}::{}", name, segment);
+        max_index = new_index;
+    }
+    Ok((Element::Variable(name), max_index))
+}
+
+fn read_symbol(chars: &[char], index: usize) -> Result<(Element, usize), ParserError> {
+    let (name, max_index) = read_name(chars, index);
+    if name.is_empty() {
+        Err(ParserError::InvalidToken(":".to_string()))
+    } else {
+        Ok((Element::Symbol(name), max_index))
+    }
+}
+
+fn read_element(
+    chars: &[char],
+    functions: &HashMap<String, Function>,
+    index: usize,
+) -> Result<(Element, usize), ParserError> {
     let mut max_index = index;
     while max_index < chars.len() {
         match chars[max_index] {
-            ' ' | '\t' | '\n' | '\r' | '\\' | '[' | ']' | '{' | '}' | '"' | '$' | '\'' => break,
+            ' ' | '\t' | '\n' | '\r' | '\\' | '[' | ']' | '{' | '}' | '"' | '$' | '\'' | ':' => break,
             _ => max_index += 1,
         }
     }
-    if let Ok(element) = Element::try_from(chars[index..max_index].iter().collect::<String>().as_str()) {
+    let token = chars[index..max_index].iter().collect::<String>();
+    if let Some(function) = functions.get(&token) {
+        Ok((Element::Function(function.clone()), max_index))
+    } else if let Ok(element) = Element::try_from(token.as_str()) {
         Ok((element, max_index))
     } else {
         Err(ParserError::EndOfInput)
@@ -133,6 +197,22 @@ pub enum ParserError {
     NotInsideProcedure,
     UnknownCharacter,
     EndOfInput,
+    InvalidMapKey(String),
+    MissingMapValue,
+}
+
+impl Display for ParserError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParserError::InvalidToken(token) => write!(f, "invalid token `{}`", token),
+            ParserError::NotInsideArray => f.write_str("`]` outside of an array"),
+            ParserError::NotInsideProcedure => f.write_str("`}` outside of a procedure"),
+            ParserError::UnknownCharacter => f.write_str("unknown character"),
+            ParserError::EndOfInput => f.write_str("unexpected end of input"),
+            ParserError::InvalidMapKey(key) => write!(f, "{} cannot be used as a map key", key),
+            ParserError::MissingMapValue => f.write_str("map literal is missing a value"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -140,6 +220,10 @@ mod tests {
     use super::*;
     use std::assert_matches::assert_matches;
 
+    fn parse(input: &str) -> Result<Vec<Element>, ParserError> {
+        super::parse(input, &HashMap::new())
+    }
+
     #[test]
     fn parses_number_as_element() {
         let result = parse("1");
@@ -192,6 +276,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_strings() {
+        let result = parse("\"abc\" \"\"");
+        assert_matches!(result, Ok(_));
+        let elements = result.unwrap();
+        assert_eq!(elements.len(), 2);
+        assert_matches!(&elements[0], Element::String(string) if string == "abc");
+        assert_matches!(&elements[1], Element::String(string) if string.is_empty());
+    }
+
+    #[test]
+    fn parses_maps() {
+        let result = parse("#{ \"a\" 1 'b' [2] 3 $x } #{}");
+        assert_matches!(result, Ok(_));
+        let elements = result.unwrap();
+        assert_eq!(elements.len(), 2);
+        assert_matches!(&elements[0], Element::Map(map) if map.len() == 3);
+        if let Element::Map(map) = &elements[0] {
+            assert_matches!(map.get(&Key::String("a".to_string())), Some(Element::Integer(1)));
+            assert_matches!(map.get(&Key::Char('b')), Some(Element::Array(_)));
+            assert_matches!(map.get(&Key::Integer(3)), Some(Element::Variable(_)));
+        }
+        assert_matches!(&elements[1], Element::Map(map) if map.is_empty());
+    }
+
+    #[test]
+    fn errs_on_invalid_maps() {
+        assert_matches!(parse("#{ 1 }"), Err(ParserError::MissingMapValue));
+        assert_matches!(parse("#{ 1.5 1 }"), Err(ParserError::InvalidMapKey(_)));
+    }
+
+    #[test]
+    fn parses_symbols() {
+        let result = parse(":red [:green]");
+        assert_matches!(result, Ok(_));
+        let elements = result.unwrap();
+        assert_eq!(elements.len(), 2);
+        assert_matches!(&elements[0], Element::Symbol(name) if name == "red");
+        assert_matches!(&elements[1], Element::Array(array) if array == &vec![Element::Symbol("green".to_string())]);
+        assert_matches!(parse(": 1"), Err(ParserError::InvalidToken(_)));
+    }
+
     #[test]
     fn parses_variables_functions_arrays() {
         let result = parse("[1 +] $inc = 2 [$inc ] . .");
@@ -206,4 +332,14 @@ mod tests {
         assert_matches!(&elements[5], Element::Function(f) if f.name() == ".");
         assert_matches!(&elements[6], Element::Function(f) if f.name() == ".");
     }
+
+    #[test]
+    fn parses_namespaced_variables() {
+        let elements = parse("$fib::fib $a::b::c $x:y").unwrap();
+        assert_eq!(elements.len(), 4);
+        assert_matches!(&elements[0], Element::Variable(name) if name == "fib::fib");
+        assert_matches!(&elements[1], Element::Variable(name) if name == "a::b::c");
+        assert_matches!(&elements[2], Element::Variable(name) if name == "x");
+        assert_matches!(&elements[3], Element::Symbol(name) if name == "y");
+    }
 }
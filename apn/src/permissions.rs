@@ -0,0 +1,155 @@
+use crate::EvaluationError;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    Filesystem,
+    Stdout,
+    Stdin,
+    EnvironmentVariables,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 4] = [
+        Capability::Filesystem,
+        Capability::Stdout,
+        Capability::Stdin,
+        Capability::EnvironmentVariables,
+    ];
+}
+
+impl Display for Capability {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Capability::Filesystem => "filesystem",
+            Capability::Stdout => "stdout",
+            Capability::Stdin => "stdin",
+            Capability::EnvironmentVariables => "environment variables",
+        })
+    }
+}
+
+/// Capabilities granted to scripts and, optionally, the directory file access is confined to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Permissions {
+    granted: HashSet<Capability>,
+    root: Option<PathBuf>,
+}
+
+impl Permissions {
+    pub fn all() -> Self {
+        Self {
+            granted: HashSet::from(Capability::ALL),
+            root: None,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self {
+            granted: HashSet::new(),
+            root: None,
+        }
+    }
+
+    pub fn allow(&mut self, capability: Capability) {
+        self.granted.insert(capability);
+    }
+
+    pub fn deny(&mut self, capability: Capability) {
+        self.granted.remove(&capability);
+    }
+
+    pub fn jail(&mut self, root: impl Into<PathBuf>) {
+        self.root = Some(root.into());
+    }
+
+    pub fn allows(&self, capability: Capability) -> bool {
+        self.granted.contains(&capability)
+    }
+
+    pub(crate) fn require(&self, capability: Capability) -> Result<(), EvaluationError> {
+        if self.allows(capability) {
+            Ok(())
+        } else {
+            Err(EvaluationError::PermissionDenied(format!("{} access", capability)))
+        }
+    }
+
+    /// Maps a script-supplied path into the root directory, treating the root as `/`.
+    pub(crate) fn path(&self, path: &str) -> Result<PathBuf, EvaluationError> {
+        self.require(Capability::Filesystem)?;
+        let Some(root) = &self.root else {
+            return Ok(PathBuf::from(path));
+        };
+        let outside = || EvaluationError::PermissionDenied(format!("{} is outside the root directory", path));
+        let mut relative = PathBuf::new();
+        for component in Path::new(path).components() {
+            match component {
+                Component::Normal(name) => relative.push(name),
+                Component::ParentDir => {
+                    if !relative.pop() {
+                        return Err(outside());
+                    }
+                }
+                Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+            }
+        }
+        let root = root.canonicalize()?;
+        let jailed = root.join(relative);
+        // `exists` follows symlinks, so a dangling link would hide behind a missing ancestor.
+        let existing = jailed.ancestors().find(|ancestor| ancestor.symlink_metadata().is_ok()).unwrap_or(&root);
+        match existing.canonicalize() {
+            Ok(real) if real.starts_with(&root) => Ok(jailed),
+            _ => Err(outside()),
+        }
+    }
+}
+
+impl Default for Permissions {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches::assert_matches;
+
+    #[test]
+    fn denies_capabilities_not_granted() {
+        let mut permissions = Permissions::none();
+        permissions.allow(Capability::Stdout);
+        assert_matches!(permissions.require(Capability::Stdout), Ok(()));
+        assert_matches!(permissions.require(Capability::Stdin), Err(EvaluationError::PermissionDenied(_)));
+        assert_matches!(permissions.path("a.apn"), Err(EvaluationError::PermissionDenied(_)));
+    }
+
+    #[test]
+    fn confines_paths_to_root_directory() {
+        let mut permissions = Permissions::all();
+        permissions.jail("../programs");
+        let root = Path::new("../programs").canonicalize().unwrap();
+        assert_eq!(permissions.path("inc.apn").unwrap(), root.join("inc.apn"));
+        assert_eq!(permissions.path("/lib/../inc.apn").unwrap(), root.join("inc.apn"));
+        assert_eq!(permissions.path("new/file.txt").unwrap(), root.join("new/file.txt"));
+        assert_matches!(permissions.path("../Cargo.toml"), Err(EvaluationError::PermissionDenied(_)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_dangling_symlinks_out_of_root() {
+        let root = std::env::temp_dir().join(format!("apn-jail-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let outside = root.with_extension("outside.txt");
+        let _ = std::fs::remove_file(&outside);
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+        let mut permissions = Permissions::all();
+        permissions.jail(&root);
+        assert_matches!(permissions.path("/link"), Err(EvaluationError::PermissionDenied(_)));
+        assert_matches!(permissions.path("/link/file.txt"), Err(EvaluationError::PermissionDenied(_)));
+    }
+}
@@ -1,6 +1,10 @@
+mod runner;
+
 use apn::Environment;
 use std::io::Write;
-use std::{env, io};
+use std::{env, io, process};
+
+const UNDO_DEPTH: usize = 32;
 
 macro_rules! print_stack {
     ($environment: tt) => {
@@ -12,13 +16,18 @@ macro_rules! print_stack {
 }
 
 fn main() -> Result<(), io::Error> {
-    let mut environment = Environment::new();
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if args.first().is_some_and(|arg| arg == "test") {
+        process::exit(if runner::run_tests(&args[1..]) { 0 } else { 1 });
+    }
+
+    let mut environment = Environment::builder().undo_depth(UNDO_DEPTH).build();
     let mut input = env::args()
         .skip(1)
         .fold(String::new(), |acc, cur| acc + " " + &cur);
     if !input.is_empty() {
         return if let Err(e) = environment.evaluate(&input) {
-            println!("{:?}", e);
+            println!("{}", e);
             Err(io::Error::new(io::ErrorKind::InvalidInput, e))
         } else {
             print_stack!(environment);
@@ -35,8 +44,15 @@ fn main() -> Result<(), io::Error> {
         if input == "exit" {
             break;
         }
+        if input == "undo" {
+            if !environment.undo() {
+                println!("Nothing to undo");
+            }
+            print_stack!(environment);
+            continue;
+        }
         if let Err(e) = environment.evaluate(&input) {
-            println!("Error evaluating: {:?}", e);
+            println!("Error evaluating: {}", e);
         }
         print_stack!(environment);
     }
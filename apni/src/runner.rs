@@ -0,0 +1,91 @@
+use apn::{Element, Environment, EvaluationError, Snapshot};
+use std::fs;
+
+pub fn run_tests(files: &[String]) -> bool {
+    let mut passed = 0;
+    let mut failed = 0;
+    for file in files {
+        let (snapshot, tests) = match load(file) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                println!("FAIL {}: {}", file, e.to_string().replace('\n', "\n  "));
+                failed += 1;
+                continue;
+            }
+        };
+        for test in tests {
+            let mut environment = Environment::new();
+            environment.restore(snapshot.clone());
+            match environment.evaluate_file(file, format!("${} .", test)) {
+                Ok(()) => {
+                    println!("PASS {}::{}", file, test);
+                    passed += 1;
+                }
+                Err(e) => {
+                    println!("FAIL {}::{}\n  {}", file, test, e.to_string().replace('\n', "\n  "));
+                    failed += 1;
+                }
+            }
+        }
+    }
+    println!("{} passed, {} failed", passed, failed);
+    failed == 0
+}
+
+/// Evaluates a test file once, returning its state and the names of its tests in order.
+fn load(file: &str) -> Result<(Snapshot, Vec<String>), EvaluationError> {
+    let source = fs::read_to_string(file).map_err(|e| EvaluationError::IO(e.to_string()))?;
+    let mut environment = Environment::new();
+    environment.evaluate_file(file, source)?;
+    let mut tests = environment
+        .vars()
+        .filter(|(name, value)| name.starts_with("test_") && matches!(value, Element::Procedure(_)))
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    tests.sort();
+    Ok((environment.snapshot(), tests))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("apni-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn write(path: &PathBuf, contents: &str) -> String {
+        fs::write(path, contents).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn discovers_tests_relative_to_their_file() {
+        let dir = directory("discover");
+        write(&dir.join("helper.apn"), "{ 2 2 + 4 assert_eq } $test_helper =");
+        let file = write(
+            &dir.join("suite.apn"),
+            "\"helper.apn\" read \"x\" \"loads.txt\" append_file { } $test_b = { } $test_a = 1 $not_a_test =",
+        );
+        assert!(run_tests(std::slice::from_ref(&file)));
+        assert_eq!(fs::read_to_string(dir.join("loads.txt")).unwrap(), "x");
+        let (_, tests) = load(&file).unwrap();
+        assert_eq!(tests, vec!["test_a", "test_b", "test_helper"]);
+    }
+
+    #[test]
+    fn fails_when_any_test_fails() {
+        let dir = directory("fail");
+        let passing = write(&dir.join("passing.apn"), "{ 1 1 assert_eq } $test_one =");
+        let failing = write(&dir.join("failing.apn"), "{ 1 2 assert_eq } $test_one = { } $test_two =");
+        let broken = write(&dir.join("broken.apn"), "1 +");
+        assert!(run_tests(std::slice::from_ref(&passing)));
+        assert!(!run_tests(&[passing.clone(), failing]));
+        assert!(!run_tests(&[passing, broken]));
+        assert!(!run_tests(&[dir.join("missing.apn").to_string_lossy().into_owned()]));
+    }
+}